@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+
+use derive_builder::Builder;
+
+use quick_xml::events::{BytesDecl, BytesText, Event};
+use quick_xml::Writer;
+
+use serde::Deserialize;
+
+use url::{form_urlencoded, Url};
+
+use crate::{ModInfo, ModLinks, Version};
+
+const ATOM_NAMESPACE: &str = "http://www.w3.org/2005/Atom";
+
+/// Feed author used when [`AtomFeedOptions::author`] is not set but some
+/// entry has no authors of its own.
+pub const ATOM_DEFAULT_AUTHOR: &str = "hk_modlinks";
+
+/// Feed-level metadata for [`ModLinks::atom_since`].
+#[derive(Debug, Clone, PartialEq, Eq, Builder)]
+#[builder(derive(Debug), setter(into, strip_option))]
+pub struct AtomFeedOptions {
+    /// IRI identifying the feed, should not change across regenerations.
+    pub id: String,
+    pub title: String,
+    /// RFC 3339 timestamp of the feed, also used for entries not found in
+    /// `entry_updated`.
+    pub updated: String,
+    /// RFC 3339 timestamps of entries already published, by entry id, so
+    /// they do not look updated again when the feed is regenerated. See
+    /// [`atom_entry_timestamps`].
+    #[builder(default)]
+    pub entry_updated: BTreeMap<String, String>,
+    /// Feed author, required by Atom unless every entry has its own authors.
+    /// Falls back to [`ATOM_DEFAULT_AUTHOR`] in that case.
+    #[builder(default)]
+    pub author: Option<String>,
+    /// Where the feed itself is published.
+    #[builder(default)]
+    pub self_link: Option<Url>,
+}
+
+impl AtomFeedOptions {
+    #[inline]
+    #[must_use]
+    pub fn builder() -> AtomFeedOptionsBuilder {
+        AtomFeedOptionsBuilder::create_empty()
+    }
+}
+
+/// Stable Atom entry id of a mod at a version.
+///
+/// Feed readers rely on this to deduplicate entries across regenerations.
+#[must_use]
+pub fn atom_entry_id(name: &str, version: &Version) -> String {
+    format!(
+        "urn:hk-modlinks:{}:{version}",
+        form_urlencoded::byte_serialize(name.as_bytes()).collect::<String>()
+    )
+}
+
+#[derive(Deserialize)]
+struct PublishedFeed {
+    #[serde(rename = "entry", default)]
+    entries: Vec<PublishedEntry>,
+}
+
+#[derive(Deserialize)]
+struct PublishedEntry {
+    id: String,
+    updated: String,
+}
+
+/// Timestamps of the entries in a previously generated Atom feed, by entry id,
+/// for [`AtomFeedOptions::entry_updated`].
+pub fn atom_entry_timestamps(feed: &str) -> Result<BTreeMap<String, String>, quick_xml::DeError> {
+    let feed: PublishedFeed = quick_xml::de::from_str(feed)?;
+
+    Ok(feed
+        .entries
+        .into_iter()
+        .map(|entry| (entry.id, entry.updated))
+        .collect())
+}
+
+impl ModLinks {
+    /// Generate an Atom feed of changes since the old modlinks, with one entry
+    /// for each new mod and each mod that received a new version. Removed mods
+    /// and changes without a version bump are not included.
+    pub fn atom_since(
+        &self,
+        old: &Self,
+        options: &AtomFeedOptions,
+    ) -> Result<String, quick_xml::Error> {
+        let diff = self.diff_since(old);
+        let entries = diff
+            .added
+            .iter()
+            .map(|(&name, &info)| (name, info, "New mod"))
+            .chain(
+                diff.version_bumps()
+                    .map(|(name, _)| (name, &self[name], "Updated")),
+            )
+            .collect::<Vec<_>>();
+        let author = options.author.as_deref().or_else(|| {
+            entries
+                .iter()
+                .any(|(_, info, _)| info.authors.is_empty())
+                .then_some(ATOM_DEFAULT_AUTHOR)
+        });
+
+        let mut writer = Writer::new_with_indent(Vec::new(), b'\t', 1);
+        writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("utf-8"), None)))?;
+
+        writer
+            .create_element("feed")
+            .with_attribute(("xmlns", ATOM_NAMESPACE))
+            .write_inner_content(|writer| {
+                write_text(writer, "id", &options.id)?;
+                write_text(writer, "title", &options.title)?;
+                write_text(writer, "updated", &options.updated)?;
+
+                if let Some(author) = author {
+                    write_author(writer, author)?;
+                }
+
+                if let Some(self_link) = &options.self_link {
+                    writer
+                        .create_element("link")
+                        .with_attributes([("rel", "self"), ("href", self_link.as_str())])
+                        .write_empty()?;
+                }
+
+                for &(name, info, kind) in &entries {
+                    write_entry(writer, name, info, kind, options)?;
+                }
+
+                Ok::<_, quick_xml::Error>(())
+            })?;
+
+        Ok(String::from_utf8(writer.into_inner()).expect("Atom feed should be valid utf-8"))
+    }
+}
+
+fn write_text<W: std::io::Write>(
+    writer: &mut Writer<W>,
+    tag: &str,
+    text: &str,
+) -> Result<(), quick_xml::Error> {
+    writer
+        .create_element(tag)
+        .write_text_content(BytesText::new(text))?;
+    Ok(())
+}
+
+fn write_author<W: std::io::Write>(
+    writer: &mut Writer<W>,
+    name: &str,
+) -> Result<(), quick_xml::Error> {
+    writer
+        .create_element("author")
+        .write_inner_content(|writer| write_text(writer, "name", name))?;
+    Ok(())
+}
+
+fn write_entry<W: std::io::Write>(
+    writer: &mut Writer<W>,
+    name: &str,
+    info: &ModInfo,
+    kind: &str,
+    options: &AtomFeedOptions,
+) -> Result<(), quick_xml::Error> {
+    let display_name = info.display_name.as_deref().unwrap_or(name);
+    let id = atom_entry_id(name, &info.version);
+    let updated = options.entry_updated.get(&id).unwrap_or(&options.updated);
+
+    writer
+        .create_element("entry")
+        .write_inner_content(|writer| {
+            write_text(writer, "id", &id)?;
+            write_text(
+                writer,
+                "title",
+                &format!("{kind}: {display_name} v{}", info.version),
+            )?;
+            write_text(writer, "updated", updated)?;
+
+            writer
+                .create_element("link")
+                .with_attributes([("rel", "alternate"), ("href", info.repository.as_str())])
+                .write_empty()?;
+
+            for author in &info.authors {
+                write_author(writer, author)?;
+            }
+
+            for tag in &info.tags {
+                writer
+                    .create_element("category")
+                    .with_attribute(("term", tag.as_str()))
+                    .write_empty()?;
+            }
+
+            write_text(writer, "summary", &info.description)
+        })?;
+
+    Ok(())
+}
@@ -1,32 +1,166 @@
-use handlebars::{Context, Handlebars, RenderError};
+use std::path::Path;
+
+use handlebars::{Context, Handlebars, RenderError, TemplateError};
 
 use lazy_static::lazy_static;
 
-use crate::ModLinksChangelog;
+use serde::Serialize;
+
+use crate::{ModLinksChangelog, ModLinksHistoryChangelog};
 
-const MARKDOWN_TEMPLATE_NAME: &str = "markdown";
+pub const MARKDOWN_TEMPLATE_NAME: &str = "markdown";
+pub const HTML_TEMPLATE_NAME: &str = "html";
+pub const PLAIN_TEMPLATE_NAME: &str = "plain";
+pub const HISTORY_MARKDOWN_TEMPLATE_NAME: &str = "history-markdown";
+/// Markdown changelog template, which uses the partials in
+/// [`MARKDOWN_PARTIALS`]. Register them alongside it when rendering it with
+/// another [`Handlebars`] registry.
 pub const CHANGELOG_TEMPLATE_MARKDOWN: &str = include_str!("./../assets/changelog-template.md");
+pub const CHANGELOG_TEMPLATE_HTML: &str = include_str!("./../assets/changelog-template.html");
+pub const CHANGELOG_TEMPLATE_PLAIN: &str = include_str!("./../assets/changelog-template.txt");
+/// Markdown history template, which uses the partials in
+/// [`MARKDOWN_PARTIALS`] like [`CHANGELOG_TEMPLATE_MARKDOWN`].
+pub const HISTORY_TEMPLATE_MARKDOWN: &str = include_str!("./../assets/history-template.md");
+
+pub const MARKDOWN_NEW_MOD_PARTIAL_NAME: &str = "markdown-new-mod";
+pub const MARKDOWN_MOD_DIFF_PARTIAL_NAME: &str = "markdown-mod-diff";
+pub const CHANGELOG_PARTIAL_MARKDOWN_NEW_MOD: &str =
+    include_str!("./../assets/changelog-partial-new-mod.md");
+pub const CHANGELOG_PARTIAL_MARKDOWN_MOD_DIFF: &str =
+    include_str!("./../assets/changelog-partial-mod-diff.md");
+
+/// Names and sources of the partials used by the Markdown templates.
+pub const MARKDOWN_PARTIALS: &[(&str, &str)] = &[
+    (
+        MARKDOWN_NEW_MOD_PARTIAL_NAME,
+        CHANGELOG_PARTIAL_MARKDOWN_NEW_MOD,
+    ),
+    (
+        MARKDOWN_MOD_DIFF_PARTIAL_NAME,
+        CHANGELOG_PARTIAL_MARKDOWN_MOD_DIFF,
+    ),
+];
 
 lazy_static! {
-    static ref HANDLEBARS_INSTANCE: Handlebars<'static> = {
-        let mut instance = Handlebars::new();
-        instance.set_strict_mode(true);
+    static ref DEFAULT_RENDERER: ChangelogRenderer<'static> = ChangelogRenderer::new();
+}
+
+/// Renders [`ModLinksChangelog`]s and [`ModLinksHistoryChangelog`]s with
+/// Handlebars templates.
+///
+/// Templates are rendered in strict mode, so referencing a field missing from
+/// the changelog context is an error carrying the template name and line. The
+/// built-in templates are always registered as [`MARKDOWN_TEMPLATE_NAME`],
+/// [`HTML_TEMPLATE_NAME`], [`PLAIN_TEMPLATE_NAME`] and
+/// [`HISTORY_MARKDOWN_TEMPLATE_NAME`], and can be used as partials along with
+/// [`MARKDOWN_PARTIALS`].
+#[derive(Debug, Clone)]
+pub struct ChangelogRenderer<'reg> {
+    registry: Handlebars<'reg>,
+}
+
+impl Default for ChangelogRenderer<'_> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'reg> ChangelogRenderer<'reg> {
+    #[must_use]
+    pub fn new() -> Self {
+        let mut registry = Handlebars::new();
+        registry.set_strict_mode(true);
 
         #[cfg(debug_assertions)]
-        instance.set_dev_mode(true);
+        registry.set_dev_mode(true);
 
-        instance
-            .register_template_string(MARKDOWN_TEMPLATE_NAME, CHANGELOG_TEMPLATE_MARKDOWN)
-            .unwrap();
+        for (name, partial) in MARKDOWN_PARTIALS {
+            registry.register_partial(name, partial).unwrap();
+        }
+
+        for (name, template) in [
+            (MARKDOWN_TEMPLATE_NAME, CHANGELOG_TEMPLATE_MARKDOWN),
+            (HTML_TEMPLATE_NAME, CHANGELOG_TEMPLATE_HTML),
+            (PLAIN_TEMPLATE_NAME, CHANGELOG_TEMPLATE_PLAIN),
+            (HISTORY_MARKDOWN_TEMPLATE_NAME, HISTORY_TEMPLATE_MARKDOWN),
+        ] {
+            registry.register_template_string(name, template).unwrap();
+        }
+
+        Self { registry }
+    }
+
+    pub fn register_template(
+        &mut self,
+        name: &str,
+        template: impl AsRef<str>,
+    ) -> Result<(), TemplateError> {
+        self.registry.register_template_string(name, template)
+    }
+
+    pub fn register_template_file(
+        &mut self,
+        name: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<(), TemplateError> {
+        self.registry.register_template_file(name, path)
+    }
+
+    pub fn register_partial(
+        &mut self,
+        name: &str,
+        partial: impl AsRef<str>,
+    ) -> Result<(), TemplateError> {
+        self.registry.register_partial(name, partial)
+    }
+
+    #[must_use]
+    pub fn has_template(&self, name: &str) -> bool {
+        self.registry.has_template(name)
+    }
 
-        instance
-    };
+    pub fn render(&self, name: &str, context: &impl Serialize) -> Result<String, RenderError> {
+        self.registry.render(name, context)
+    }
+
+    /// Render with a one-off template, which can use any registered template
+    /// or partial.
+    pub fn render_template(
+        &self,
+        template: &str,
+        context: &impl Serialize,
+    ) -> Result<String, RenderError> {
+        self.registry.render_template(template, context)
+    }
 }
 
 impl ModLinksChangelog {
     #[inline]
     pub fn to_markdown(&self) -> Result<String, RenderError> {
-        HANDLEBARS_INSTANCE.render(MARKDOWN_TEMPLATE_NAME, self.json())
+        DEFAULT_RENDERER.render(MARKDOWN_TEMPLATE_NAME, self)
+    }
+
+    #[inline]
+    pub fn to_html(&self) -> Result<String, RenderError> {
+        DEFAULT_RENDERER.render(HTML_TEMPLATE_NAME, self)
+    }
+
+    #[inline]
+    pub fn to_plain(&self) -> Result<String, RenderError> {
+        DEFAULT_RENDERER.render(PLAIN_TEMPLATE_NAME, self)
+    }
+
+    #[inline]
+    pub fn render_with(&self, template: &str) -> Result<String, RenderError> {
+        DEFAULT_RENDERER.render_template(template, self)
+    }
+}
+
+impl ModLinksHistoryChangelog {
+    #[inline]
+    pub fn to_markdown(&self) -> Result<String, RenderError> {
+        DEFAULT_RENDERER.render(HISTORY_MARKDOWN_TEMPLATE_NAME, self)
     }
 }
 
@@ -36,3 +170,10 @@ impl From<ModLinksChangelog> for Context {
         value.ctx.into()
     }
 }
+
+impl From<ModLinksHistoryChangelog> for Context {
+    #[inline]
+    fn from(value: ModLinksHistoryChangelog) -> Self {
+        value.ctx.into()
+    }
+}
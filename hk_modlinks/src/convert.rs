@@ -1,4 +1,4 @@
-use crate::{ApiLinks, ModInfo, ModLinks};
+use crate::{ApiLinks, Installation, ModInfo, ModLinks};
 
 #[cfg(feature = "ron")]
 #[inline(always)]
@@ -94,6 +94,8 @@ macro_rules! impl_convert {
 
 impl_convert!(ApiLinks);
 
+impl_convert!(Installation);
+
 impl_convert!(ModInfo);
 
 impl_convert!(ModLinks);
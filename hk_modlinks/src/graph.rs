@@ -0,0 +1,250 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use derive_builder::Builder;
+
+use crate::{MissingMod, ModLinks, ResolveError, Tag};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum GraphFormat {
+    /// Graphviz DOT
+    #[default]
+    Dot,
+    Mermaid,
+}
+
+/// Options for [`ModLinks::to_graph`].
+#[derive(Debug, Clone, PartialEq, Eq, Builder)]
+#[builder(derive(Debug), setter(into, strip_option))]
+pub struct GraphOptions {
+    /// Only include these mods and their dependencies, all mods are included
+    /// when empty. Mods they integrate with are included, but not followed
+    /// further.
+    #[builder(default, setter(each(name = "root", into)))]
+    pub roots: Vec<String>,
+    #[builder(default = "true")]
+    pub integrations: bool,
+    /// Fill nodes with a color for their first tag.
+    #[builder(default)]
+    pub color_by_tag: bool,
+}
+
+impl Default for GraphOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            roots: vec![],
+            integrations: true,
+            color_by_tag: false,
+        }
+    }
+}
+
+impl GraphOptions {
+    #[inline]
+    #[must_use]
+    pub fn builder() -> GraphOptionsBuilder {
+        GraphOptionsBuilder::create_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum EdgeKind {
+    Dependency,
+    Integration,
+}
+
+struct Graph<'a> {
+    nodes: BTreeSet<&'a str>,
+    edges: BTreeSet<(&'a str, &'a str, EdgeKind)>,
+}
+
+impl<'a> Graph<'a> {
+    fn new(mod_links: &'a ModLinks, options: &'a GraphOptions) -> Result<Self, ResolveError<'a>> {
+        let mut mods: BTreeSet<&str> = if options.roots.is_empty() {
+            mod_links.mod_names().map(String::as_str).collect()
+        } else {
+            let missing = options
+                .roots
+                .iter()
+                .map(String::as_str)
+                .filter(|root| !mod_links.contains(root))
+                .collect::<BTreeSet<_>>();
+            if !missing.is_empty() {
+                return Err(ResolveError {
+                    missing: missing
+                        .into_iter()
+                        .map(|name| MissingMod { chain: vec![name] })
+                        .collect(),
+                });
+            }
+
+            // Missing dependencies are not followed, but still become nodes
+            // through their edges
+            let mut mods = BTreeSet::new();
+            let mut queue = options.roots.iter().map(String::as_str).collect::<Vec<_>>();
+            while let Some(name) = queue.pop() {
+                if let Some(info) = mod_links.get(name) {
+                    if mods.insert(name) {
+                        queue.extend(info.dependencies.iter().map(String::as_str));
+                    }
+                }
+            }
+            mods
+        };
+
+        let mut edges = BTreeSet::new();
+        for &name in &mods {
+            let info = &mod_links[name];
+
+            for dep in &info.dependencies {
+                edges.insert((name, dep.as_str(), EdgeKind::Dependency));
+            }
+
+            if options.integrations {
+                for integration in &info.integrations {
+                    edges.insert((name, integration.as_str(), EdgeKind::Integration));
+                }
+            }
+        }
+
+        mods.extend(edges.iter().map(|&(_, to, _)| to));
+
+        Ok(Self { nodes: mods, edges })
+    }
+}
+
+fn tag_color(tag: Tag) -> &'static str {
+    match tag {
+        Tag::Boss => "#f4a582",
+        Tag::Cosmetic => "#f7c6e0",
+        Tag::Expansion => "#b8e186",
+        Tag::Gameplay => "#92c5de",
+        Tag::Library => "#d9d9d9",
+        Tag::Utility => "#fee08b",
+    }
+}
+
+fn node_tag(mod_links: &ModLinks, name: &str, options: &GraphOptions) -> Option<Tag> {
+    if !options.color_by_tag {
+        return None;
+    }
+
+    mod_links.get(name)?.tags.first().copied()
+}
+
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_mermaid(s: &str) -> String {
+    s.replace('"', "#quot;")
+}
+
+impl ModLinks {
+    /// Render the dependency graph, with an edge from each mod to what it
+    /// depends on or integrates with. Integration edges are dashed, and mods
+    /// missing from the modlinks are drawn with dotted borders.
+    ///
+    /// Fails only if one of [`GraphOptions::roots`] is not in the modlinks.
+    pub fn to_graph<'a>(
+        &'a self,
+        format: GraphFormat,
+        options: &'a GraphOptions,
+    ) -> Result<String, ResolveError<'a>> {
+        let graph = Graph::new(self, options)?;
+
+        Ok(match format {
+            GraphFormat::Dot => self.render_dot(&graph, options),
+            GraphFormat::Mermaid => self.render_mermaid(&graph, options),
+        })
+    }
+
+    #[inline]
+    pub fn to_dot<'a>(&'a self, options: &'a GraphOptions) -> Result<String, ResolveError<'a>> {
+        self.to_graph(GraphFormat::Dot, options)
+    }
+
+    #[inline]
+    pub fn to_mermaid<'a>(&'a self, options: &'a GraphOptions) -> Result<String, ResolveError<'a>> {
+        self.to_graph(GraphFormat::Mermaid, options)
+    }
+
+    fn render_dot(&self, graph: &Graph<'_>, options: &GraphOptions) -> String {
+        let mut out = String::from("digraph modlinks {\n\tnode [shape=box];\n");
+
+        for &name in &graph.nodes {
+            let label = self.get_display_name(name).unwrap_or(name);
+            write!(
+                out,
+                "\t\"{}\" [label=\"{}\"",
+                escape_dot(name),
+                escape_dot(label)
+            )
+            .unwrap();
+            if !self.contains(name) {
+                out.push_str(", style=dotted");
+            } else if let Some(tag) = node_tag(self, name, options) {
+                write!(out, ", style=filled, fillcolor=\"{}\"", tag_color(tag)).unwrap();
+            }
+            out.push_str("];\n");
+        }
+
+        for &(from, to, kind) in &graph.edges {
+            write!(out, "\t\"{}\" -> \"{}\"", escape_dot(from), escape_dot(to)).unwrap();
+            if kind == EdgeKind::Integration {
+                out.push_str(" [style=dashed]");
+            }
+            out.push_str(";\n");
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    fn render_mermaid(&self, graph: &Graph<'_>, options: &GraphOptions) -> String {
+        // Mod names may contain characters Mermaid does not accept in ids
+        let ids: BTreeMap<&str, String> = graph
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, &name)| (name, format!("n{i}")))
+            .collect();
+        let mut classes: BTreeMap<Tag, Vec<&str>> = BTreeMap::new();
+        let mut missing = vec![];
+
+        let mut out = String::from("graph LR\n");
+
+        for &name in &graph.nodes {
+            let label = self.get_display_name(name).unwrap_or(name);
+            writeln!(out, "\t{}[\"{}\"]", ids[name], escape_mermaid(label)).unwrap();
+            if !self.contains(name) {
+                missing.push(ids[name].as_str());
+            } else if let Some(tag) = node_tag(self, name, options) {
+                classes.entry(tag).or_default().push(&ids[name]);
+            }
+        }
+
+        for &(from, to, kind) in &graph.edges {
+            let arrow = match kind {
+                EdgeKind::Dependency => "-->",
+                EdgeKind::Integration => "-.->",
+            };
+            writeln!(out, "\t{} {arrow} {}", ids[from], ids[to]).unwrap();
+        }
+
+        for (tag, nodes) in classes {
+            let class = tag.as_str().to_lowercase();
+            writeln!(out, "\tclassDef {class} fill:{}", tag_color(tag)).unwrap();
+            writeln!(out, "\tclass {} {class}", nodes.join(",")).unwrap();
+        }
+
+        if !missing.is_empty() {
+            writeln!(out, "\tclassDef missing stroke-dasharray:3").unwrap();
+            writeln!(out, "\tclass {} missing", missing.join(",")).unwrap();
+        }
+
+        out
+    }
+}
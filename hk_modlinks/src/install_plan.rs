@@ -0,0 +1,172 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use serde::Serialize;
+
+use crate::{ModLinks, ResolveError, ResolveOptions, SkippedIntegration};
+
+/// Mods to install in order, with dependencies before their dependents.
+///
+/// Mods not depending on each other are ordered by name, so the same input
+/// always produces the same plan. Mods in a dependency cycle are ordered by
+/// name when no other mod can be installed, and mods depending on the cycle
+/// still come after it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct InstallPlan<'a>(Vec<&'a str>);
+
+impl<'a> InstallPlan<'a> {
+    /// Build a plan from mods with all of their dependencies.
+    pub fn new<'b: 'a>(
+        mod_links: &'a ModLinks,
+        iter: impl IntoIterator<Item = &'b str>,
+    ) -> Result<Self, ResolveError<'a>> {
+        Ok(Self::from_resolved(
+            mod_links,
+            &mod_links.resolve_deps(iter)?,
+        ))
+    }
+
+    /// Order already resolved mods, such as [`Resolution::mods`]. Dependencies
+    /// outside of the resolved mods are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the mods is not in the modlinks.
+    #[must_use]
+    pub fn from_resolved(mod_links: &'a ModLinks, resolved: &HashSet<&'a str>) -> Self {
+        let mut pending: BTreeMap<&'a str, usize> = BTreeMap::new();
+        let mut dependents: BTreeMap<&'a str, Vec<&'a str>> = BTreeMap::new();
+
+        for &name in resolved {
+            let deps = mod_links[name]
+                .dependencies
+                .iter()
+                .filter(|dep| resolved.contains(dep.as_str()))
+                .collect::<Vec<_>>();
+            pending.insert(name, deps.len());
+
+            for dep in deps {
+                dependents.entry(dep.as_str()).or_default().push(name);
+            }
+        }
+
+        let mut ready: BTreeSet<&'a str> = pending
+            .iter()
+            .filter(|(_, &count)| count == 0)
+            .map(|(&name, _)| name)
+            .collect();
+        let mut order = Vec::with_capacity(pending.len());
+
+        while !pending.is_empty() {
+            let name = match ready.pop_first() {
+                Some(name) => name,
+                None => find_cycle_member(mod_links, &pending),
+            };
+
+            pending.remove(name);
+            order.push(name);
+
+            for &dependent in dependents.get(name).into_iter().flatten() {
+                if let Some(count) = pending.get_mut(dependent) {
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.insert(dependent);
+                    }
+                }
+            }
+        }
+
+        Self(order)
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn as_slice(&self) -> &[&'a str] {
+        &self.0
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn into_vec(self) -> Vec<&'a str> {
+        self.0
+    }
+
+    #[inline]
+    pub fn iter(&self) -> std::iter::Copied<std::slice::Iter<'_, &'a str>> {
+        self.0.iter().copied()
+    }
+}
+
+impl<'a> IntoIterator for InstallPlan<'a> {
+    type IntoIter = std::vec::IntoIter<&'a str>;
+    type Item = <Self::IntoIter as Iterator>::Item;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, 'b> IntoIterator for &'b InstallPlan<'a> {
+    type IntoIter = std::iter::Copied<std::slice::Iter<'b, &'a str>>;
+    type Item = <Self::IntoIter as Iterator>::Item;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Walk pending dependencies from the first pending mod until a mod is seen
+/// twice, and return the first by name in the cycle found.
+fn find_cycle_member<'a>(mod_links: &'a ModLinks, pending: &BTreeMap<&'a str, usize>) -> &'a str {
+    let mut path = vec![];
+    let mut current = *pending.keys().next().unwrap();
+
+    while !path.contains(&current) {
+        path.push(current);
+        current = mod_links[current]
+            .dependencies
+            .iter()
+            .map(String::as_str)
+            .find(|dep| pending.contains_key(dep))
+            .expect("pending mod should have a pending dependency");
+    }
+
+    let start = path.iter().position(|&name| name == current).unwrap();
+    path.drain(start..).min().unwrap()
+}
+
+impl ModLinks {
+    #[inline]
+    pub fn install_plan<'a, 'b: 'a>(
+        &'a self,
+        iter: impl IntoIterator<Item = &'b str>,
+    ) -> Result<InstallPlan<'a>, ResolveError<'a>> {
+        InstallPlan::new(self, iter)
+    }
+
+    /// Like [`ModLinks::install_plan`], but resolved with options. Skipped
+    /// integrations are returned along with the plan.
+    pub fn install_plan_with<'a, 'b: 'a>(
+        &'a self,
+        iter: impl IntoIterator<Item = &'b str>,
+        options: &ResolveOptions,
+    ) -> Result<(InstallPlan<'a>, Vec<SkippedIntegration<'a>>), ResolveError<'a>> {
+        let resolution = self.resolve_with(iter, options)?;
+        Ok((
+            InstallPlan::from_resolved(self, &resolution.mods),
+            resolution.skipped,
+        ))
+    }
+}
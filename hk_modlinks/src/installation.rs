@@ -0,0 +1,192 @@
+use std::collections::{btree_map, BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+use serde_with::{formats::Uppercase, hex::Hex, serde_as};
+
+use crate::{FileDef, MissingMod, ModInfo, ModLinks, Version};
+
+/// Mod installed into a game from a modlinks.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct InstalledMod {
+    pub version: Version,
+    /// SHA256 of the archive the mod was installed from.
+    #[serde_as(as = "Hex<Uppercase>")]
+    pub sha256: [u8; 32],
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub dependencies: BTreeSet<String>,
+}
+
+impl InstalledMod {
+    /// Record of a mod installed from `file`, one of its links.
+    #[must_use]
+    pub fn new(mod_info: &ModInfo, file: &FileDef) -> Self {
+        Self {
+            version: mod_info.version.clone(),
+            sha256: file.sha256,
+            dependencies: mod_info.dependencies.clone(),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn sha256(&self) -> String {
+        hex::encode_upper(self.sha256)
+    }
+}
+
+/// Mods installed into a game, by name.
+///
+/// Meant to be kept next to the mods, so that the installation can be
+/// compared with a modlinks later on.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Installation(BTreeMap<String, InstalledMod>);
+
+impl FromIterator<(String, InstalledMod)> for Installation {
+    fn from_iter<T: IntoIterator<Item = (String, InstalledMod)>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<'a> IntoIterator for &'a Installation {
+    type IntoIter = btree_map::Iter<'a, String, InstalledMod>;
+    type Item = <Self::IntoIter as Iterator>::Item;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl Installation {
+    #[must_use]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn contains(&self, name: impl AsRef<str>) -> bool {
+        self.0.contains_key(name.as_ref())
+    }
+
+    pub fn get(&self, name: impl AsRef<str>) -> Option<&InstalledMod> {
+        self.0.get(name.as_ref())
+    }
+
+    pub fn insert(&mut self, name: String, installed: InstalledMod) -> Option<InstalledMod> {
+        self.0.insert(name, installed)
+    }
+
+    pub fn remove(&mut self, name: impl AsRef<str>) -> Option<InstalledMod> {
+        self.0.remove(name.as_ref())
+    }
+
+    pub fn mod_names(&self) -> btree_map::Keys<'_, String, InstalledMod> {
+        self.0.keys()
+    }
+
+    pub fn iter(&self) -> btree_map::Iter<'_, String, InstalledMod> {
+        self.0.iter()
+    }
+
+    /// Installed mods depending on the mod, as recorded when they were
+    /// installed.
+    pub fn dependents<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> {
+        self.0
+            .iter()
+            .filter(move |(_, installed)| installed.dependencies.contains(name))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Compare installed mods with the modlinks.
+    ///
+    /// A mod needs an update if the modlinks has a greater [`Version`] of it.
+    /// Dependencies of installed mods in the modlinks that are not installed
+    /// are reported as well, since updating brings them in. Mods depending on
+    /// mods missing from the modlinks cannot be updated, and are reported
+    /// along with what they miss instead.
+    #[must_use]
+    pub fn outdated<'a>(&'a self, mod_links: &'a ModLinks) -> Outdated<'a> {
+        let (present, missing): (Vec<_>, Vec<_>) = self
+            .mod_names()
+            .map(String::as_str)
+            .partition(|name| mod_links.contains(name));
+
+        let mut outdated = Outdated {
+            missing,
+            ..Default::default()
+        };
+
+        // Resolved one by one, so a broken mod does not hide other updates
+        let mut resolved = vec![];
+        for name in present {
+            match mod_links.resolve_deps_single(name) {
+                Ok(_) => resolved.push(name),
+                Err(e) => outdated.missing_dependencies.extend(e.missing),
+            }
+        }
+
+        let plan = mod_links
+            .install_plan(resolved)
+            .expect("Dependencies should have been resolved");
+        for name in &plan {
+            let available = &mod_links[name].version;
+
+            match self.get(name) {
+                Some(installed) if *available > installed.version => {
+                    outdated.updates.push(ModUpdate {
+                        name,
+                        installed: &installed.version,
+                        available,
+                    });
+                }
+                Some(_) => {}
+                None => outdated.new_dependencies.push(name),
+            }
+        }
+
+        outdated
+    }
+}
+
+/// Installed mod with a greater version in the modlinks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModUpdate<'a> {
+    pub name: &'a str,
+    pub installed: &'a Version,
+    pub available: &'a Version,
+}
+
+/// Difference between an [`Installation`] and a modlinks, as found by
+/// [`Installation::outdated`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Outdated<'a> {
+    /// Mods with a greater version available, in install order.
+    pub updates: Vec<ModUpdate<'a>>,
+    /// Mods that installed mods depend on but are not installed, in install
+    /// order.
+    pub new_dependencies: Vec<&'a str>,
+    /// Installed mods not in the modlinks.
+    pub missing: Vec<&'a str>,
+    /// Dependencies missing from the modlinks, each with the chain from the
+    /// installed mod requiring it. Such mods are left out of `updates`.
+    pub missing_dependencies: Vec<MissingMod<'a>>,
+}
+
+impl Outdated<'_> {
+    /// Whether nothing needs to be updated or installed.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.updates.is_empty() && self.new_dependencies.is_empty()
+    }
+}
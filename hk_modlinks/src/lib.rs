@@ -2,11 +2,20 @@
 
 mod api_links;
 mod file_def;
+mod graph;
+mod install_plan;
+mod installation;
 mod links;
+mod lint;
 mod mod_info;
 mod mod_links;
+mod mod_links_dependents;
+mod mod_links_diff;
+mod mod_links_history;
 mod platform;
+mod resolve;
 mod tag;
+mod validation;
 mod version;
 
 #[cfg(feature = "xml")]
@@ -15,6 +24,9 @@ mod xml;
 #[cfg(feature = "convert")]
 mod convert;
 
+#[cfg(feature = "atom")]
+mod atom;
+
 #[cfg(feature = "changelog")]
 mod mod_links_changelog;
 
@@ -23,13 +35,25 @@ mod changelog_template;
 
 pub use api_links::*;
 pub use file_def::*;
+pub use graph::*;
+pub use install_plan::*;
+pub use installation::*;
 pub use links::*;
+pub use lint::*;
 pub use mod_info::*;
 pub use mod_links::*;
+pub use mod_links_dependents::*;
+pub use mod_links_diff::*;
+pub use mod_links_history::*;
 pub use platform::*;
+pub use resolve::*;
 pub use tag::*;
+pub use validation::*;
 pub use version::*;
 
+#[cfg(feature = "atom")]
+pub use atom::*;
+
 #[cfg(feature = "changelog")]
 pub use mod_links_changelog::*;
 
@@ -63,9 +87,9 @@ pub fn is_valid_file_url(url: &url::Url) -> bool {
 ///
 /// Steps in detail:
 /// 1. Split the string into parts by characters: ` ` (space), `[` and `]`,
-/// empty parts are discarded throughout the process;
+///    empty parts are discarded throughout the process;
 /// 2. Remove all characters that are neither ascii-alphanumeric nor one of
-/// `.`, `-`, `_`;
+///    `.`, `-`, `_`;
 /// 4. Convert first character in each part to ascii uppercase;
 /// 5. Join all the parts together.
 #[must_use]
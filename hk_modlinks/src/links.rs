@@ -34,9 +34,26 @@ impl Links {
             linux: Box::new(linux),
         }
     }
+
+    /// All files with their platform, `None` for a universal file.
+    #[must_use]
+    pub fn files(&self) -> Vec<(Option<Platform>, &FileDef)> {
+        match self {
+            Self::Universal(file) => vec![(None, file)],
+            Self::PlatformSpecific {
+                windows,
+                mac,
+                linux,
+            } => vec![
+                (Some(Platform::Windows), windows),
+                (Some(Platform::Mac), mac),
+                (Some(Platform::Linux), linux),
+            ],
+        }
+    }
 }
 
-#[cfg(any(target_os = "windows", target_os = "mac", target_os = "linux"))]
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
 impl Links {
     #[must_use]
     pub fn file(&self, platform: Option<Platform>) -> &FileDef {
@@ -0,0 +1,365 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Debug};
+
+use serde::{Deserialize, Serialize};
+
+use thiserror::Error;
+
+use crate::{ModInfo, ModLinks, Severity, ValidationIssue, ValidationReport};
+
+/// Maximum description length in characters used by `description-too-long`
+/// when not configured.
+pub const DEFAULT_DESCRIPTION_MAX_LENGTH: usize = 500;
+
+/// A problem reported by a [`LintRule`] for a mod.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintFinding {
+    pub field: String,
+    pub message: String,
+}
+
+impl LintFinding {
+    #[inline]
+    #[must_use]
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// A named check of modlinks content quality, run on each mod.
+pub trait LintRule: Send + Sync {
+    /// Kebab-case name used in configuration and as the issue code.
+    fn name(&self) -> &'static str;
+
+    fn description(&self) -> &'static str;
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, name: &str, info: &ModInfo, config: &RuleConfig) -> Vec<LintFinding>;
+}
+
+/// Configuration of a single rule, unset fields fall back to the defaults of
+/// the rule.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct RuleConfig {
+    pub enabled: Option<bool>,
+    pub severity: Option<Severity>,
+    /// Used by length limiting rules.
+    pub max_length: Option<usize>,
+}
+
+/// Lint configuration, e.g. in TOML:
+///
+/// ```toml
+/// [rules.description-too-long]
+/// severity = "error"
+/// max-length = 200
+///
+/// [rules.missing-issues]
+/// enabled = false
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LintConfig {
+    pub rules: BTreeMap<String, RuleConfig>,
+}
+
+#[cfg(feature = "toml")]
+impl LintConfig {
+    #[inline]
+    pub fn from_toml(s: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(s)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum LintConfigError {
+    #[error("Unknown lint rule: {0}")]
+    UnknownRule(String),
+}
+
+/// Runs [`LintRule`]s over modlinks according to a [`LintConfig`].
+pub struct Linter {
+    rules: Vec<Box<dyn LintRule>>,
+    config: LintConfig,
+}
+
+impl Debug for Linter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Linter")
+            .field(
+                "rules",
+                &self.rules.iter().map(|r| r.name()).collect::<Vec<_>>(),
+            )
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Linter {
+    /// Linter with all built-in rules and default configuration.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::new_empty().with_rules(builtin_rules())
+    }
+
+    /// Linter without any rules.
+    #[must_use]
+    pub fn new_empty() -> Self {
+        Self {
+            rules: vec![],
+            config: Default::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_rules(mut self, rules: impl IntoIterator<Item = Box<dyn LintRule>>) -> Self {
+        self.rules.extend(rules);
+        self
+    }
+
+    /// Add a rule, replacing any rule with the same name.
+    pub fn add_rule(&mut self, rule: Box<dyn LintRule>) {
+        self.rules.retain(|r| r.name() != rule.name());
+        self.rules.push(rule);
+    }
+
+    pub fn rules(&self) -> impl Iterator<Item = &dyn LintRule> {
+        self.rules.iter().map(AsRef::as_ref)
+    }
+
+    /// Set configuration, all configured rules must already be added.
+    pub fn configure(&mut self, config: LintConfig) -> Result<(), LintConfigError> {
+        if let Some(name) = config
+            .rules
+            .keys()
+            .find(|name| !self.rules.iter().any(|r| r.name() == name.as_str()))
+        {
+            return Err(LintConfigError::UnknownRule(name.clone()));
+        }
+
+        self.config = config;
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn is_enabled(&self, rule: &dyn LintRule) -> bool {
+        self.rule_config(rule).enabled.unwrap_or(true)
+    }
+
+    #[must_use]
+    pub fn severity(&self, rule: &dyn LintRule) -> Severity {
+        self.rule_config(rule)
+            .severity
+            .unwrap_or_else(|| rule.default_severity())
+    }
+
+    fn rule_config(&self, rule: &dyn LintRule) -> &RuleConfig {
+        const DEFAULT: &RuleConfig = &RuleConfig {
+            enabled: None,
+            severity: None,
+            max_length: None,
+        };
+
+        self.config.rules.get(rule.name()).unwrap_or(DEFAULT)
+    }
+
+    #[must_use]
+    pub fn lint(&self, mod_links: &ModLinks) -> ValidationReport {
+        let mut report = ValidationReport::new();
+
+        for (name, info) in mod_links {
+            for rule in self.rules().filter(|r| self.is_enabled(*r)) {
+                let severity = self.severity(rule);
+                report.extend(
+                    rule.check(name, info, self.rule_config(rule))
+                        .into_iter()
+                        .map(|finding| {
+                            ValidationIssue::new(
+                                severity,
+                                rule.name(),
+                                name,
+                                finding.field,
+                                finding.message,
+                            )
+                        }),
+                );
+            }
+        }
+
+        report
+    }
+}
+
+impl ModLinks {
+    /// Lint with all built-in rules and default configuration.
+    #[inline]
+    #[must_use]
+    pub fn lint(&self) -> ValidationReport {
+        Linter::new().lint(self)
+    }
+}
+
+#[must_use]
+pub fn builtin_rules() -> Vec<Box<dyn LintRule>> {
+    vec![
+        Box::new(EmptyDescription),
+        Box::new(DescriptionTooLong),
+        Box::new(RedundantDisplayName),
+        Box::new(MissingIssues),
+        Box::new(NoTags),
+        Box::new(NonHttpsRepository),
+    ]
+}
+
+macro_rules! finding {
+	($cond:expr, $field:literal, $($arg:tt)+) => {
+		if $cond {
+			vec![LintFinding::new($field, format!($($arg)+))]
+		} else {
+			vec![]
+		}
+	};
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EmptyDescription;
+
+impl LintRule for EmptyDescription {
+    fn name(&self) -> &'static str {
+        "empty-description"
+    }
+
+    fn description(&self) -> &'static str {
+        "Description is empty or only whitespace"
+    }
+
+    fn check(&self, _: &str, info: &ModInfo, _: &RuleConfig) -> Vec<LintFinding> {
+        finding!(
+            info.description.trim().is_empty(),
+            "description",
+            "Description is empty"
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DescriptionTooLong;
+
+impl LintRule for DescriptionTooLong {
+    fn name(&self) -> &'static str {
+        "description-too-long"
+    }
+
+    fn description(&self) -> &'static str {
+        "Description is longer than max-length characters"
+    }
+
+    fn check(&self, _: &str, info: &ModInfo, config: &RuleConfig) -> Vec<LintFinding> {
+        let max_length = config.max_length.unwrap_or(DEFAULT_DESCRIPTION_MAX_LENGTH);
+        let length = info.description.chars().count();
+
+        finding!(
+            length > max_length,
+            "description",
+            "Description is {length} characters long, exceeding {max_length}"
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RedundantDisplayName;
+
+impl LintRule for RedundantDisplayName {
+    fn name(&self) -> &'static str {
+        "redundant-display-name"
+    }
+
+    fn description(&self) -> &'static str {
+        "Display name is the same as the mod name"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn check(&self, name: &str, info: &ModInfo, _: &RuleConfig) -> Vec<LintFinding> {
+        finding!(
+            info.display_name.as_deref() == Some(name),
+            "display_name",
+            "Display name is the same as the mod name"
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MissingIssues;
+
+impl LintRule for MissingIssues {
+    fn name(&self) -> &'static str {
+        "missing-issues"
+    }
+
+    fn description(&self) -> &'static str {
+        "No issues URL"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn check(&self, _: &str, info: &ModInfo, _: &RuleConfig) -> Vec<LintFinding> {
+        finding!(info.issues.is_none(), "issues", "No issues URL")
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct NoTags;
+
+impl LintRule for NoTags {
+    fn name(&self) -> &'static str {
+        "no-tags"
+    }
+
+    fn description(&self) -> &'static str {
+        "Mod has no tags"
+    }
+
+    fn check(&self, _: &str, info: &ModInfo, _: &RuleConfig) -> Vec<LintFinding> {
+        finding!(info.tags.is_empty(), "tags", "No tags")
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct NonHttpsRepository;
+
+impl LintRule for NonHttpsRepository {
+    fn name(&self) -> &'static str {
+        "non-https-repository"
+    }
+
+    fn description(&self) -> &'static str {
+        "Repository URL does not use https"
+    }
+
+    fn check(&self, _: &str, info: &ModInfo, _: &RuleConfig) -> Vec<LintFinding> {
+        finding!(
+            info.repository.scheme() != "https",
+            "repository",
+            "Repository URL {} does not use https",
+            info.repository
+        )
+    }
+}
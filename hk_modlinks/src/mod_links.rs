@@ -1,4 +1,4 @@
-use std::collections::{btree_map, BTreeMap, BTreeSet, HashSet};
+use std::collections::{btree_map, BTreeMap, BTreeSet, HashSet, VecDeque};
 use std::ops::{Index, IndexMut};
 
 use serde::{Deserialize, Serialize};
@@ -123,7 +123,7 @@ impl ModLinks {
         self.0.get_mut(name.as_ref())
     }
 
-    pub fn get_display_name<'a>(&'a self, name: &'a str) -> Option<&str> {
+    pub fn get_display_name<'a>(&'a self, name: &'a str) -> Option<&'a str> {
         self.0
             .get(name)
             .map(|info| info.display_name.as_deref().unwrap_or(name))
@@ -157,43 +157,6 @@ impl ModLinks {
         self.0.iter_mut()
     }
 
-    pub fn resolve_deps_single<'a>(
-        &'a self,
-        name: &'a str,
-    ) -> Result<HashSet<&'a str>, Vec<&'a str>> {
-        self.resolve_deps(std::iter::once(name))
-    }
-
-    pub fn resolve_deps<'a, 'b: 'a>(
-        &'a self,
-        iter: impl IntoIterator<Item = &'b str>,
-    ) -> Result<HashSet<&'a str>, Vec<&'a str>> {
-        let mut to_resolve: BTreeSet<&'a str> = iter.into_iter().collect();
-        let mut resolved: HashSet<&'a str> = Default::default();
-        let mut unknown = vec![];
-
-        while let Some(name) = to_resolve.pop_first() {
-            let Some(mod_info) = self.get(name) else {
-                unknown.push(name);
-                continue;
-            };
-
-            if !resolved.insert(name) {
-                continue;
-            }
-
-            for dep in mod_info.dependencies.iter() {
-                to_resolve.insert(dep);
-            }
-        }
-
-        if unknown.is_empty() {
-            Ok(resolved)
-        } else {
-            Err(unknown)
-        }
-    }
-
     pub fn validate_names(&self) -> Result<(), Vec<&str>> {
         let invalid: Vec<_> = self
             .mod_names()
@@ -232,11 +195,123 @@ impl ModLinks {
         }
     }
 
+    /// Check that no mod depends on itself through its dependencies,
+    /// integrations are not considered.
+    ///
+    /// One cycle is reported for each group of mods depending on each other,
+    /// as the shortest path from the first mod in the group by name back to
+    /// itself. The path starts with that mod, and each mod depends on the next
+    /// one, with the last depending on the first.
+    pub fn validate_acyclic(&self) -> Result<(), Vec<Vec<&str>>> {
+        let cycles: Vec<_> = strongly_connected_components(self)
+            .into_iter()
+            .filter_map(|component| shortest_cycle(self, &component))
+            .collect();
+
+        if cycles.is_empty() {
+            Ok(())
+        } else {
+            Err(cycles)
+        }
+    }
+
     pub fn merge(&mut self, other: Self) {
         self.extend(other);
     }
 }
 
+/// Tarjan's algorithm over dependencies, components are sorted by their first
+/// mod.
+fn strongly_connected_components(mod_links: &ModLinks) -> Vec<BTreeSet<&str>> {
+    #[derive(Default)]
+    struct State<'a> {
+        index: BTreeMap<&'a str, usize>,
+        low_link: BTreeMap<&'a str, usize>,
+        stack: Vec<&'a str>,
+        on_stack: HashSet<&'a str>,
+        components: Vec<BTreeSet<&'a str>>,
+    }
+
+    fn visit<'a>(mod_links: &'a ModLinks, state: &mut State<'a>, name: &'a str) {
+        let index = state.index.len();
+        state.index.insert(name, index);
+        state.low_link.insert(name, index);
+        state.stack.push(name);
+        state.on_stack.insert(name);
+
+        for dep in &mod_links[name].dependencies {
+            let dep = dep.as_str();
+            if !mod_links.contains(dep) {
+                continue;
+            }
+
+            if !state.index.contains_key(dep) {
+                visit(mod_links, state, dep);
+                let low_link = state.low_link[name].min(state.low_link[dep]);
+                state.low_link.insert(name, low_link);
+            } else if state.on_stack.contains(dep) {
+                let low_link = state.low_link[name].min(state.index[dep]);
+                state.low_link.insert(name, low_link);
+            }
+        }
+
+        if state.low_link[name] == index {
+            let mut component = BTreeSet::new();
+            while let Some(member) = state.stack.pop() {
+                state.on_stack.remove(member);
+                component.insert(member);
+                if member == name {
+                    break;
+                }
+            }
+            state.components.push(component);
+        }
+    }
+
+    let mut state = State::default();
+    for name in mod_links.mod_names() {
+        if !state.index.contains_key(name.as_str()) {
+            visit(mod_links, &mut state, name);
+        }
+    }
+
+    state.components.sort();
+    state.components
+}
+
+/// Shortest cycle through the first mod of a strongly connected component, if
+/// the component has any cycle.
+fn shortest_cycle<'a>(
+    mod_links: &'a ModLinks,
+    component: &BTreeSet<&'a str>,
+) -> Option<Vec<&'a str>> {
+    let &start = component.first()?;
+    let mut previous: BTreeMap<&str, &str> = BTreeMap::new();
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(name) = queue.pop_front() {
+        for dep in &mod_links[name].dependencies {
+            let dep = dep.as_str();
+
+            if dep == start {
+                let mut path = vec![name];
+                while let Some(&prev) = path.last().and_then(|last| previous.get(last)) {
+                    path.push(prev);
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            if component.contains(dep) && !previous.contains_key(dep) {
+                previous.insert(dep, name);
+                queue.push_back(dep);
+            }
+        }
+    }
+
+    None
+}
+
 #[cfg(feature = "xml")]
 impl ModLinks {
     #[inline]
@@ -1,23 +1,37 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use serde::Serialize;
 
 use serde_json::{json, Value as JsonValue};
 
-use crate::ModLinks;
+use crate::{FieldChange, Links, LinksDiff, ModInfoDiff, ModLinks, ModLinksDiff, ModLinksHistory};
 
+/// JSON Schema of the changelog context returned by [`ModLinksChangelog::json`].
+pub const CHANGELOG_JSON_SCHEMA: &str = include_str!("./../assets/changelog-schema.json");
+
+/// Changelog between two [`ModLinks`], kept as a JSON context for rendering.
+///
+/// The context follows [`CHANGELOG_JSON_SCHEMA`]: existing fields are stable,
+/// while new fields may be added in later versions.
 pub struct ModLinksChangelog {
     pub(crate) ctx: JsonValue,
 }
 
 impl ModLinksChangelog {
+    #[inline]
     #[must_use]
     pub(crate) fn new(old: &ModLinks, new: &ModLinks) -> Self {
+        Self::from_diff(&ModLinksDiff::new(old, new))
+    }
+
+    #[must_use]
+    pub fn from_diff(diff: &ModLinksDiff<'_>) -> Self {
         Self {
             ctx: json!({
-                "new": empty_map_to_null(Self::new_mods(old, new)),
-                "removed": Self::removed_mods(old, new),
-                "updated": empty_map_to_null(Self::updated_mods(old, new))
+                "new": empty_map_to_null(Self::new_mods(diff)),
+                "removed": Self::removed_mods(diff),
+                "updated": empty_map_to_null(Self::updated_mods(diff)),
+                "changed": empty_map_to_null(Self::changed_mods(diff))
             }),
         }
     }
@@ -27,6 +41,88 @@ impl ModLinksChangelog {
     pub fn json(&self) -> &JsonValue {
         &self.ctx
     }
+
+    #[inline]
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.ctx)
+    }
+}
+
+impl From<&ModLinksDiff<'_>> for ModLinksChangelog {
+    #[inline]
+    fn from(value: &ModLinksDiff<'_>) -> Self {
+        Self::from_diff(value)
+    }
+}
+
+/// Changelog across a series of snapshots, kept as a JSON context for
+/// rendering.
+///
+/// The context has two fields: `steps`, an array of objects with the
+/// `snapshot` and `previous` labels and the `changelog` of that step following
+/// [`CHANGELOG_JSON_SCHEMA`], and `timelines`, an object mapping mod names to
+/// the serialized [`TimelineEntry`](crate::TimelineEntry)s of [`ModLinksHistory::timelines`].
+pub struct ModLinksHistoryChangelog {
+    pub(crate) ctx: JsonValue,
+}
+
+impl ModLinksHistoryChangelog {
+    #[must_use]
+    pub fn from_history(history: &ModLinksHistory<'_>) -> Self {
+        let steps = history
+            .steps
+            .iter()
+            .map(|step| {
+                json!({
+                    "snapshot": step.snapshot,
+                    "previous": step.previous,
+                    "changelog": ModLinksChangelog::from_diff(&step.diff).ctx
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            ctx: json!({
+                "steps": steps,
+                "timelines": to_json_value(&history.timelines)
+            }),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn json(&self) -> &JsonValue {
+        &self.ctx
+    }
+
+    #[inline]
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.ctx)
+    }
+}
+
+impl From<&ModLinksHistory<'_>> for ModLinksHistoryChangelog {
+    #[inline]
+    fn from(value: &ModLinksHistory<'_>) -> Self {
+        Self::from_history(value)
+    }
+}
+
+impl Serialize for ModLinksHistoryChangelog {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.json().serialize(serializer)
+    }
+}
+
+impl ModLinksHistory<'_> {
+    #[inline]
+    #[must_use]
+    pub fn changelog(&self) -> ModLinksHistoryChangelog {
+        ModLinksHistoryChangelog::from_history(self)
+    }
 }
 
 impl From<ModLinksChangelog> for JsonValue {
@@ -58,69 +154,60 @@ fn empty_map_to_null(value: JsonValue) -> JsonValue {
 
 impl ModLinksChangelog {
     #[inline]
-    fn new_mods(old: &ModLinks, new: &ModLinks) -> JsonValue {
+    fn new_mods(diff: &ModLinksDiff<'_>) -> JsonValue {
         to_json_value(
-            new.iter()
-                .filter_map(|(name, mod_info)| {
-                    if old.contains(name) {
-                        None
-                    } else {
-                        Some((
-                            name,
-                            json!({
-                                "description": mod_info.description,
-                                "dependencies": mod_info.dependencies,
-                                "integrations": mod_info.integrations,
-                                "tags": mod_info.tags
-                            }),
-                        ))
-                    }
+            diff.added
+                .iter()
+                .map(|(name, mod_info)| {
+                    (
+                        name,
+                        json!({
+                            "display_name": mod_info.display_name,
+                            "description": mod_info.description,
+                            "version": mod_info.version,
+                            "dependencies": mod_info.dependencies,
+                            "repository": mod_info.repository,
+                            "issues": mod_info.issues,
+                            "integrations": mod_info.integrations,
+                            "tags": mod_info.tags,
+                            "authors": mod_info.authors
+                        }),
+                    )
                 })
                 .collect::<BTreeMap<_, _>>(),
         )
     }
 
     #[inline]
-    fn removed_mods(old: &ModLinks, new: &ModLinks) -> JsonValue {
-        to_json_value(
-            old.iter()
-                .filter_map(
-                    |(name, _)| {
-                        if new.contains(name) {
-                            None
-                        } else {
-                            Some(name)
-                        }
-                    },
-                )
-                .collect::<Vec<_>>(),
-        )
+    fn removed_mods(diff: &ModLinksDiff<'_>) -> JsonValue {
+        to_json_value(diff.removed.keys().collect::<Vec<_>>())
     }
 
     #[inline]
-    fn gen_old_new(old: &str, new: &str) -> JsonValue {
-        if old == new {
-            JsonValue::Null
-        } else {
-            json!({
+    fn gen_old_new<T: Serialize>(change: Option<FieldChange<T>>) -> JsonValue {
+        match change {
+            Some(FieldChange { old, new }) => json!({
                 "old": old,
                 "new": new
-            })
+            }),
+            None => JsonValue::Null,
         }
     }
 
     #[inline]
-    fn gen_removed_added<T: Serialize>(removed: Vec<T>, added: Vec<T>) -> JsonValue {
-        let removed_empty = removed.is_empty();
-        let added_empty = added.is_empty();
-
-        if removed_empty && added_empty {
+    fn gen_removed_added<T: Ord + Serialize>(
+        change: Option<FieldChange<&BTreeSet<T>>>,
+    ) -> JsonValue {
+        let Some(change) = change else {
             return JsonValue::Null;
-        }
+        };
+
+        let removed = change.removed().collect::<Vec<_>>();
+        let added = change.added().collect::<Vec<_>>();
 
-        if removed_empty {
+        if removed.is_empty() {
             json!({ "added": added })
-        } else if added_empty {
+        } else if added.is_empty() {
             json!({ "removed": removed })
         } else {
             json!({
@@ -131,38 +218,102 @@ impl ModLinksChangelog {
     }
 
     #[inline]
-    fn updated_mods(old: &ModLinks, new: &ModLinks) -> JsonValue {
+    fn gen_links(change: Option<FieldChange<&Links>>) -> JsonValue {
+        let Some(change) = change else {
+            return JsonValue::Null;
+        };
+
+        let (kind, files) = match change.details() {
+            LinksDiff::Universal(file) => (None, BTreeMap::from([("Universal", json!(file))])),
+            LinksDiff::PlatformSpecific {
+                windows,
+                mac,
+                linux,
+            } => (
+                None,
+                [("Windows", windows), ("Mac", mac), ("Linux", linux)]
+                    .into_iter()
+                    .filter(|(_, file)| !file.is_empty())
+                    .map(|(platform, file)| (platform, json!(file)))
+                    .collect(),
+            ),
+            LinksDiff::UniversalToPlatformSpecific => (
+                Some(FieldChange::new("universal", "platform-specific")),
+                Self::gen_new_files(change.new),
+            ),
+            LinksDiff::PlatformSpecificToUniversal => (
+                Some(FieldChange::new("platform-specific", "universal")),
+                Self::gen_new_files(change.new),
+            ),
+        };
+
+        json!({
+            "kind": kind,
+            "files": files
+        })
+    }
+
+    /// Files of links that replaced links of another kind, with nothing to
+    /// compare them to.
+    #[inline]
+    fn gen_new_files(links: &Links) -> BTreeMap<&'static str, JsonValue> {
+        let files = match links {
+            Links::Universal(file) => vec![("Universal", file)],
+            Links::PlatformSpecific {
+                windows,
+                mac,
+                linux,
+            } => vec![
+                ("Windows", &**windows),
+                ("Mac", &**mac),
+                ("Linux", &**linux),
+            ],
+        };
+
+        files
+            .into_iter()
+            .map(|(platform, file)| {
+                (
+                    platform,
+                    json!({
+                        "sha256": FieldChange::new(None, Some(file.sha256())),
+                        "url": FieldChange::new(None, Some(&file.url))
+                    }),
+                )
+            })
+            .collect()
+    }
+
+    #[inline]
+    fn gen_mod_diff(diff: &ModInfoDiff<'_>) -> JsonValue {
+        json!({
+            "display_name": Self::gen_old_new(diff.display_name),
+            "description": Self::gen_old_new(diff.description),
+            "version": Self::gen_old_new(diff.version),
+            "links": Self::gen_links(diff.links),
+            "dependencies": Self::gen_removed_added(diff.dependencies),
+            "repository": Self::gen_old_new(diff.repository),
+            "issues": Self::gen_old_new(diff.issues),
+            "integrations": Self::gen_removed_added(diff.integrations),
+            "tags": Self::gen_removed_added(diff.tags),
+            "authors": Self::gen_removed_added(diff.authors)
+        })
+    }
+
+    #[inline]
+    fn updated_mods(diff: &ModLinksDiff<'_>) -> JsonValue {
         to_json_value(
-            new
-                .iter()
-                .filter_map(|(name, new)| {
-					let old = old.get(name)?;
-
-					let ver = Self::gen_old_new(old.version.str(), new.version.str());
-					if ver.is_null() {
-						return None;
-					}
-
-                    Some((
-						name,
-						json!({
-							"version": ver,
-							"description": Self::gen_old_new(old.description.as_str(), new.description.as_str()),
-							"dependencies": Self::gen_removed_added(
-								old.dependencies.difference(&new.dependencies).collect::<Vec<_>>(),
-								new.dependencies.difference(&old.dependencies).collect::<Vec<_>>()
-							),
-							"integrations": Self::gen_removed_added(
-								old.integrations.difference(&new.integrations).collect::<Vec<_>>(),
-								new.integrations.difference(&old.integrations).collect::<Vec<_>>()
-							),
-							"tags": Self::gen_removed_added(
-								new.tags.difference(&old.tags).collect::<Vec<_>>(),
-								old.tags.difference(&new.tags).collect::<Vec<_>>()
-							)
-						}),
-					))
-                })
+            diff.version_bumps()
+                .map(|(name, diff)| (name, Self::gen_mod_diff(diff)))
+                .collect::<BTreeMap<_, _>>(),
+        )
+    }
+
+    #[inline]
+    fn changed_mods(diff: &ModLinksDiff<'_>) -> JsonValue {
+        to_json_value(
+            diff.unversioned_changes()
+                .map(|(name, diff)| (name, Self::gen_mod_diff(diff)))
                 .collect::<BTreeMap<_, _>>(),
         )
     }
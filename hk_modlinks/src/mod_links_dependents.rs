@@ -0,0 +1,131 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use crate::ModLinks;
+
+/// Mods that refer to a mod, split by how they refer to it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Dependents<'a> {
+    /// Mods listing it as a dependency.
+    pub required_by: BTreeSet<&'a str>,
+    /// Mods listing it as an integration, excluding those in `required_by`.
+    pub integrated_by: BTreeSet<&'a str>,
+}
+
+impl Dependents<'_> {
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.required_by.is_empty() && self.integrated_by.is_empty()
+    }
+}
+
+/// Reverse dependency index of a [`ModLinks`], for answering repeated
+/// "what depends on X" queries.
+///
+/// Names that are referred to but not present in the modlinks are indexed as
+/// well.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DependentsIndex<'a> {
+    required_by: BTreeMap<&'a str, BTreeSet<&'a str>>,
+    integrated_by: BTreeMap<&'a str, BTreeSet<&'a str>>,
+}
+
+impl<'a> DependentsIndex<'a> {
+    #[must_use]
+    pub fn new(mod_links: &'a ModLinks) -> Self {
+        let mut index = Self::default();
+
+        for (name, info) in mod_links {
+            for dep in &info.dependencies {
+                index
+                    .required_by
+                    .entry(dep.as_str())
+                    .or_default()
+                    .insert(name.as_str());
+            }
+
+            for integration in &info.integrations {
+                index
+                    .integrated_by
+                    .entry(integration.as_str())
+                    .or_default()
+                    .insert(name.as_str());
+            }
+        }
+
+        index
+    }
+
+    /// Mods directly depending on or integrating with the mod.
+    #[must_use]
+    pub fn dependents(&self, name: &str) -> Dependents<'a> {
+        let required_by = self.required_by.get(name).cloned().unwrap_or_default();
+        let integrated_by = self
+            .integrated_by
+            .get(name)
+            .map(|set| set.difference(&required_by).copied().collect())
+            .unwrap_or_default();
+
+        Dependents {
+            required_by,
+            integrated_by,
+        }
+    }
+
+    /// Mods depending on the mod either directly or through other mods, and
+    /// mods integrating with any of them.
+    ///
+    /// Integrations are not followed further, as losing an integration does
+    /// not break a mod.
+    #[must_use]
+    pub fn dependents_transitive(&self, name: &str) -> Dependents<'a> {
+        let mut required_by = BTreeSet::new();
+        let mut to_visit = vec![name];
+
+        while let Some(current) = to_visit.pop() {
+            for &dependent in self.required_by.get(current).into_iter().flatten() {
+                if dependent != name && required_by.insert(dependent) {
+                    to_visit.push(dependent);
+                }
+            }
+        }
+
+        let integrated_by = std::iter::once(name)
+            .chain(required_by.iter().copied())
+            .filter_map(|i| self.integrated_by.get(i))
+            .flatten()
+            .copied()
+            .filter(|i| *i != name && !required_by.contains(i))
+            .collect();
+
+        Dependents {
+            required_by,
+            integrated_by,
+        }
+    }
+}
+
+impl ModLinks {
+    #[inline]
+    #[must_use]
+    pub fn dependents_index(&self) -> DependentsIndex<'_> {
+        DependentsIndex::new(self)
+    }
+
+    /// See [`DependentsIndex::dependents`]. Build the index with
+    /// [`ModLinks::dependents_index`] instead when querying more than once.
+    #[inline]
+    #[must_use]
+    pub fn dependents(&self, name: &str) -> Dependents<'_> {
+        self.dependents_index().dependents(name)
+    }
+
+    /// See [`DependentsIndex::dependents_transitive`].
+    #[inline]
+    #[must_use]
+    pub fn dependents_transitive(&self, name: &str) -> Dependents<'_> {
+        self.dependents_index().dependents_transitive(name)
+    }
+}
@@ -0,0 +1,298 @@
+use std::collections::{btree_set, BTreeMap, BTreeSet};
+
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+
+use url::Url;
+
+use crate::{FileDef, Links, ModInfo, ModLinks, Tag, Version};
+
+/// A change of a single field, holding both the old and the new value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FieldChange<T> {
+    pub old: T,
+    pub new: T,
+}
+
+impl<T: PartialEq> FieldChange<T> {
+    #[must_use]
+    pub fn new(old: T, new: T) -> Self {
+        Self { old, new }
+    }
+
+    /// Returns `None` if the two values are equal.
+    #[must_use]
+    pub fn between(old: T, new: T) -> Option<Self> {
+        if old == new {
+            None
+        } else {
+            Some(Self::new(old, new))
+        }
+    }
+}
+
+impl<'a, T: Ord> FieldChange<&'a BTreeSet<T>> {
+    /// Items present in the old set but not in the new one.
+    pub fn removed(&self) -> btree_set::Difference<'a, T> {
+        self.old.difference(self.new)
+    }
+
+    /// Items present in the new set but not in the old one.
+    pub fn added(&self) -> btree_set::Difference<'a, T> {
+        self.new.difference(self.old)
+    }
+}
+
+impl<'a> FieldChange<&'a Links> {
+    /// Break the change down into changes of individual files.
+    #[must_use]
+    pub fn details(&self) -> LinksDiff<'a> {
+        match (self.old, self.new) {
+            (Links::Universal(old), Links::Universal(new)) => {
+                LinksDiff::Universal(FileDefDiff::new(old, new))
+            }
+            (
+                Links::PlatformSpecific {
+                    windows: old_windows,
+                    mac: old_mac,
+                    linux: old_linux,
+                },
+                Links::PlatformSpecific {
+                    windows: new_windows,
+                    mac: new_mac,
+                    linux: new_linux,
+                },
+            ) => LinksDiff::PlatformSpecific {
+                windows: FileDefDiff::new(old_windows, new_windows),
+                mac: FileDefDiff::new(old_mac, new_mac),
+                linux: FileDefDiff::new(old_linux, new_linux),
+            },
+            (Links::Universal(_), Links::PlatformSpecific { .. }) => {
+                LinksDiff::UniversalToPlatformSpecific
+            }
+            (Links::PlatformSpecific { .. }, Links::Universal(_)) => {
+                LinksDiff::PlatformSpecificToUniversal
+            }
+        }
+    }
+
+    /// Test if the links only differ in their SHA256, i.e. the files were
+    /// re-uploaded to the same urls.
+    #[must_use]
+    pub fn is_sha256_only(&self) -> bool {
+        match self.details() {
+            LinksDiff::Universal(file) => file.url.is_none(),
+            LinksDiff::PlatformSpecific {
+                windows,
+                mac,
+                linux,
+            } => windows.url.is_none() && mac.url.is_none() && linux.url.is_none(),
+            _ => false,
+        }
+    }
+}
+
+/// Difference between two revisions of a [`FileDef`].
+///
+/// Each field is `None` if it is unchanged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FileDefDiff<'a> {
+    pub sha256: Option<FieldChange<&'a [u8; 32]>>,
+    pub url: Option<FieldChange<&'a Url>>,
+}
+
+impl<'a> FileDefDiff<'a> {
+    #[must_use]
+    pub fn new(old: &'a FileDef, new: &'a FileDef) -> Self {
+        Self {
+            sha256: FieldChange::between(&old.sha256, &new.sha256),
+            url: FieldChange::between(&old.url, &new.url),
+        }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.sha256.is_none() && self.url.is_none()
+    }
+}
+
+impl Serialize for FileDefDiff<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut ser = serializer.serialize_struct("FileDefDiff", 2)?;
+        ser.serialize_field(
+            "sha256",
+            &self.sha256.map(|change| {
+                FieldChange::new(hex::encode_upper(change.old), hex::encode_upper(change.new))
+            }),
+        )?;
+        ser.serialize_field("url", &self.url)?;
+        ser.end()
+    }
+}
+
+/// Per-file view of a change of [`Links`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum LinksDiff<'a> {
+    Universal(FileDefDiff<'a>),
+    PlatformSpecific {
+        windows: FileDefDiff<'a>,
+        mac: FileDefDiff<'a>,
+        linux: FileDefDiff<'a>,
+    },
+    UniversalToPlatformSpecific,
+    PlatformSpecificToUniversal,
+}
+
+/// Field-by-field difference between two revisions of a [`ModInfo`].
+///
+/// Each field is `None` if it is unchanged.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ModInfoDiff<'a> {
+    pub display_name: Option<FieldChange<Option<&'a str>>>,
+    pub description: Option<FieldChange<&'a str>>,
+    pub version: Option<FieldChange<&'a Version>>,
+    pub links: Option<FieldChange<&'a Links>>,
+    pub dependencies: Option<FieldChange<&'a BTreeSet<String>>>,
+    pub repository: Option<FieldChange<&'a Url>>,
+    pub issues: Option<FieldChange<Option<&'a Url>>>,
+    pub integrations: Option<FieldChange<&'a BTreeSet<String>>>,
+    pub tags: Option<FieldChange<&'a BTreeSet<Tag>>>,
+    pub authors: Option<FieldChange<&'a BTreeSet<String>>>,
+}
+
+impl<'a> ModInfoDiff<'a> {
+    #[must_use]
+    pub fn new(old: &'a ModInfo, new: &'a ModInfo) -> Self {
+        // Destruct first to ensure new fields get compared
+        let ModInfo {
+            display_name,
+            description,
+            version,
+            links,
+            dependencies,
+            repository,
+            issues,
+            integrations,
+            tags,
+            authors,
+        } = new;
+
+        Self {
+            display_name: FieldChange::between(
+                old.display_name.as_deref(),
+                display_name.as_deref(),
+            ),
+            description: FieldChange::between(old.description.as_str(), description.as_str()),
+            version: FieldChange::between(&old.version, version),
+            links: FieldChange::between(&old.links, links),
+            dependencies: FieldChange::between(&old.dependencies, dependencies),
+            repository: FieldChange::between(&old.repository, repository),
+            issues: FieldChange::between(old.issues.as_ref(), issues.as_ref()),
+            integrations: FieldChange::between(&old.integrations, integrations),
+            tags: FieldChange::between(&old.tags, tags),
+            authors: FieldChange::between(&old.authors, authors),
+        }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_version_bump(&self) -> bool {
+        self.version.is_some()
+    }
+
+    /// Test if nothing but the SHA256 of the download links changed, which
+    /// usually means the mod was re-uploaded under the same version.
+    #[must_use]
+    pub fn is_sha256_only(&self) -> bool {
+        match &self.links {
+            Some(links) => {
+                links.is_sha256_only()
+                    && Self {
+                        links: None,
+                        ..self.clone()
+                    }
+                    .is_empty()
+            }
+            None => false,
+        }
+    }
+}
+
+/// Difference between two [`ModLinks`], keyed by mod name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ModLinksDiff<'a> {
+    pub added: BTreeMap<&'a str, &'a ModInfo>,
+    pub removed: BTreeMap<&'a str, &'a ModInfo>,
+    pub updated: BTreeMap<&'a str, ModInfoDiff<'a>>,
+}
+
+impl<'a> ModLinksDiff<'a> {
+    #[must_use]
+    pub fn new(old: &'a ModLinks, new: &'a ModLinks) -> Self {
+        let added = new
+            .iter()
+            .filter(|(name, _)| !old.contains(name))
+            .map(|(name, info)| (name.as_str(), info))
+            .collect();
+
+        let removed = old
+            .iter()
+            .filter(|(name, _)| !new.contains(name))
+            .map(|(name, info)| (name.as_str(), info))
+            .collect();
+
+        let updated = new
+            .iter()
+            .filter_map(|(name, new_info)| {
+                let diff = ModInfoDiff::new(old.get(name)?, new_info);
+                (!diff.is_empty()).then_some((name.as_str(), diff))
+            })
+            .collect();
+
+        Self {
+            added,
+            removed,
+            updated,
+        }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.updated.is_empty()
+    }
+
+    /// Updated mods that received a new version.
+    pub fn version_bumps(&self) -> impl Iterator<Item = (&'a str, &ModInfoDiff<'a>)> {
+        self.updated
+            .iter()
+            .filter(|(_, diff)| diff.is_version_bump())
+            .map(|(name, diff)| (*name, diff))
+    }
+
+    /// Updated mods that changed without a new version.
+    pub fn unversioned_changes(&self) -> impl Iterator<Item = (&'a str, &ModInfoDiff<'a>)> {
+        self.updated
+            .iter()
+            .filter(|(_, diff)| !diff.is_version_bump())
+            .map(|(name, diff)| (*name, diff))
+    }
+}
+
+impl ModLinks {
+    #[inline]
+    #[must_use]
+    pub fn diff_since<'a>(&'a self, old: &'a Self) -> ModLinksDiff<'a> {
+        ModLinksDiff::new(old, self)
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn diff_until<'a>(&'a self, new: &'a Self) -> ModLinksDiff<'a> {
+        new.diff_since(self)
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::{FieldChange, ModLinks, ModLinksDiff, Version};
+
+/// Changes between a snapshot and the one before it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HistoryStep<'a> {
+    pub snapshot: &'a str,
+    pub previous: &'a str,
+    pub diff: ModLinksDiff<'a>,
+}
+
+/// Something that happened to a mod in a snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum ModEvent<'a> {
+    /// Already present in the first snapshot.
+    Initial {
+        version: &'a Version,
+    },
+    Added {
+        version: &'a Version,
+    },
+    Updated {
+        version: FieldChange<&'a Version>,
+    },
+    Removed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TimelineEntry<'a> {
+    pub snapshot: &'a str,
+    #[serde(flatten)]
+    pub event: ModEvent<'a>,
+}
+
+/// History across an ordered series of [`ModLinks`] snapshots.
+///
+/// Only version changes make it into the per-mod timelines, other changes
+/// can be found in the [`ModLinksDiff`] of each step.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ModLinksHistory<'a> {
+    pub steps: Vec<HistoryStep<'a>>,
+    pub timelines: BTreeMap<&'a str, Vec<TimelineEntry<'a>>>,
+}
+
+impl<'a> ModLinksHistory<'a> {
+    /// Build history from snapshots labelled by the first element, oldest
+    /// first.
+    #[must_use]
+    pub fn new(snapshots: impl IntoIterator<Item = (&'a str, &'a ModLinks)>) -> Self {
+        let mut history = Self::default();
+        let mut snapshots = snapshots.into_iter();
+
+        let Some((mut previous, mut previous_mod_links)) = snapshots.next() else {
+            return history;
+        };
+
+        for (name, info) in previous_mod_links {
+            history.push_event(
+                name.as_str(),
+                previous,
+                ModEvent::Initial {
+                    version: &info.version,
+                },
+            );
+        }
+
+        for (snapshot, mod_links) in snapshots {
+            let diff = ModLinksDiff::new(previous_mod_links, mod_links);
+
+            for (&name, &info) in &diff.added {
+                history.push_event(
+                    name,
+                    snapshot,
+                    ModEvent::Added {
+                        version: &info.version,
+                    },
+                );
+            }
+
+            for &name in diff.removed.keys() {
+                history.push_event(name, snapshot, ModEvent::Removed);
+            }
+
+            for (name, info_diff) in diff.version_bumps() {
+                history.push_event(
+                    name,
+                    snapshot,
+                    ModEvent::Updated {
+                        version: info_diff.version.unwrap(),
+                    },
+                );
+            }
+
+            history.steps.push(HistoryStep {
+                snapshot,
+                previous,
+                diff,
+            });
+
+            previous = snapshot;
+            previous_mod_links = mod_links;
+        }
+
+        history
+    }
+
+    #[inline]
+    fn push_event(&mut self, name: &'a str, snapshot: &'a str, event: ModEvent<'a>) {
+        self.timelines
+            .entry(name)
+            .or_default()
+            .push(TimelineEntry { snapshot, event });
+    }
+
+    #[must_use]
+    pub fn timeline(&self, name: impl AsRef<str>) -> Option<&[TimelineEntry<'a>]> {
+        self.timelines.get(name.as_ref()).map(Vec::as_slice)
+    }
+}
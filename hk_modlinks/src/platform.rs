@@ -17,7 +17,7 @@ impl Display for Platform {
     }
 }
 
-#[cfg(any(target_os = "windows", target_os = "mac", target_os = "linux"))]
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
 impl Default for Platform {
     #[inline]
     fn default() -> Self {
@@ -0,0 +1,272 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+use std::fmt::{self, Display};
+
+use derive_builder::Builder;
+
+use serde::Serialize;
+
+use thiserror::Error;
+
+use crate::ModLinks;
+
+/// A mod that could not be found while resolving dependencies.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MissingMod<'a> {
+    /// Shortest dependency chain from a requested mod to the missing one,
+    /// both included. Displayed as `Root -> Dependency -> Missing`.
+    pub chain: Vec<&'a str>,
+}
+
+impl<'a> MissingMod<'a> {
+    #[inline]
+    #[must_use]
+    pub fn name(&self) -> &'a str {
+        self.chain.last().unwrap()
+    }
+
+    /// The mod that was requested and led to the missing one.
+    #[inline]
+    #[must_use]
+    pub fn root(&self) -> &'a str {
+        self.chain.first().unwrap()
+    }
+}
+
+impl Display for MissingMod<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.root())?;
+        for name in &self.chain[1..] {
+            write!(f, " -> {name}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub struct ResolveError<'a> {
+    /// Missing mods ordered by name.
+    pub missing: Vec<MissingMod<'a>>,
+}
+
+impl<'a> ResolveError<'a> {
+    pub fn names(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.missing.iter().map(MissingMod::name)
+    }
+}
+
+impl Display for ResolveError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Unknown mods:")?;
+        for missing in &self.missing {
+            write!(f, "\n  {missing}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Options for [`ModLinks::resolve_with`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Builder)]
+#[builder(derive(Debug), setter(into, strip_option))]
+pub struct ResolveOptions {
+    /// Also resolve mods that requested mods integrate with, when they and
+    /// their dependencies are available.
+    #[builder(default)]
+    pub integrations: bool,
+    /// Follow integrations of every resolved mod instead of only requested
+    /// ones.
+    #[builder(default)]
+    pub transitive_integrations: bool,
+    /// Mods that are left out along with dependencies only they bring in,
+    /// e.g. because they are already installed. Requested mods are never
+    /// excluded.
+    #[builder(default, setter(each(name = "exclude", into)))]
+    pub excluded: BTreeSet<String>,
+}
+
+impl ResolveOptions {
+    #[inline]
+    #[must_use]
+    pub fn builder() -> ResolveOptionsBuilder {
+        ResolveOptionsBuilder::create_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason<'a> {
+    Excluded,
+    /// The integration or one of its dependencies is not in the modlinks.
+    Unavailable(ResolveError<'a>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedIntegration<'a> {
+    pub name: &'a str,
+    /// The first mod found integrating with it.
+    pub integrated_by: &'a str,
+    pub reason: SkipReason<'a>,
+}
+
+impl Display for SkippedIntegration<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (integration of {}): ", self.name, self.integrated_by)?;
+        match &self.reason {
+            SkipReason::Excluded => f.write_str("excluded"),
+            SkipReason::Unavailable(err) => {
+                f.write_str("unavailable, missing ")?;
+                for (i, missing) in err.missing.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{missing}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Resolution<'a> {
+    pub mods: HashSet<&'a str>,
+    /// Integrations that were considered but not resolved, ordered by name.
+    pub skipped: Vec<SkippedIntegration<'a>>,
+}
+
+impl ModLinks {
+    pub fn resolve_deps_single<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> Result<HashSet<&'a str>, ResolveError<'a>> {
+        self.resolve_deps(std::iter::once(name))
+    }
+
+    #[inline]
+    pub fn resolve_deps<'a, 'b: 'a>(
+        &'a self,
+        iter: impl IntoIterator<Item = &'b str>,
+    ) -> Result<HashSet<&'a str>, ResolveError<'a>> {
+        self.resolve_deps_excluding(iter, &BTreeSet::new())
+    }
+
+    /// Resolve mods with their dependencies, and integrations according to
+    /// options.
+    pub fn resolve_with<'a, 'b: 'a>(
+        &'a self,
+        iter: impl IntoIterator<Item = &'b str>,
+        options: &ResolveOptions,
+    ) -> Result<Resolution<'a>, ResolveError<'a>> {
+        let roots: BTreeSet<&'a str> = iter.into_iter().collect();
+        let mut mods = self.resolve_deps_excluding(roots.iter().copied(), &options.excluded)?;
+        let mut skipped: BTreeMap<&'a str, SkippedIntegration<'a>> = BTreeMap::new();
+
+        if !options.integrations {
+            return Ok(Resolution {
+                mods,
+                skipped: vec![],
+            });
+        }
+
+        let mut to_check = if options.transitive_integrations {
+            mods.iter().copied().collect()
+        } else {
+            roots
+        };
+
+        while let Some(name) = to_check.pop_first() {
+            let Some(info) = self.get(name) else {
+                continue;
+            };
+
+            for integration in &info.integrations {
+                let integration = integration.as_str();
+                if mods.contains(integration) || skipped.contains_key(integration) {
+                    continue;
+                }
+
+                let resolved = if options.excluded.contains(integration) {
+                    Err(SkipReason::Excluded)
+                } else {
+                    self.resolve_deps_excluding([integration], &options.excluded)
+                        .map_err(SkipReason::Unavailable)
+                };
+
+                match resolved {
+                    Ok(resolved) => {
+                        for name in resolved {
+                            if mods.insert(name) && options.transitive_integrations {
+                                to_check.insert(name);
+                            }
+                        }
+                    }
+                    Err(reason) => {
+                        skipped.insert(
+                            integration,
+                            SkippedIntegration {
+                                name: integration,
+                                integrated_by: name,
+                                reason,
+                            },
+                        );
+                    }
+                }
+            }
+        }
+
+        // An integration skipped for one mod may be brought in by another
+        skipped.retain(|name, _| !mods.contains(name));
+
+        Ok(Resolution {
+            mods,
+            skipped: skipped.into_values().collect(),
+        })
+    }
+
+    fn resolve_deps_excluding<'a, 'b: 'a>(
+        &'a self,
+        iter: impl IntoIterator<Item = &'b str>,
+        excluded: &BTreeSet<String>,
+    ) -> Result<HashSet<&'a str>, ResolveError<'a>> {
+        let roots: BTreeSet<&'a str> = iter.into_iter().collect();
+        // Where each mod is first required from, roots have none
+        let mut required_by: BTreeMap<&'a str, Option<&'a str>> =
+            roots.iter().map(|&root| (root, None)).collect();
+        let mut to_resolve: VecDeque<&'a str> = roots.into_iter().collect();
+        let mut resolved: HashSet<&'a str> = Default::default();
+        let mut unknown = BTreeSet::new();
+
+        while let Some(name) = to_resolve.pop_front() {
+            let Some(mod_info) = self.get(name) else {
+                unknown.insert(name);
+                continue;
+            };
+
+            resolved.insert(name);
+
+            for dep in mod_info.dependencies.iter() {
+                if !required_by.contains_key(dep.as_str()) && !excluded.contains(dep) {
+                    required_by.insert(dep, Some(name));
+                    to_resolve.push_back(dep);
+                }
+            }
+        }
+
+        if unknown.is_empty() {
+            return Ok(resolved);
+        }
+
+        let missing = unknown
+            .into_iter()
+            .map(|name| {
+                let mut chain = vec![name];
+                while let Some(&Some(parent)) = chain.last().and_then(|last| required_by.get(last))
+                {
+                    chain.push(parent);
+                }
+                chain.reverse();
+                MissingMod { chain }
+            })
+            .collect();
+
+        Err(ResolveError { missing })
+    }
+}
@@ -0,0 +1,377 @@
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{is_valid_file_url, is_valid_mod_name, ModLinks};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Display for Severity {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// A problem found in a mod.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    /// Kebab-case identifier of the kind of problem, e.g. `unknown-dependency`.
+    pub code: String,
+    pub mod_name: String,
+    /// Offending field of the mod, e.g. `dependencies` or `links.windows`.
+    pub field: String,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    #[must_use]
+    pub fn new(
+        severity: Severity,
+        code: impl Into<String>,
+        mod_name: impl Into<String>,
+        field: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            severity,
+            code: code.into(),
+            mod_name: mod_name.into(),
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn error(
+        code: impl Into<String>,
+        mod_name: impl Into<String>,
+        field: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self::new(Severity::Error, code, mod_name, field, message)
+    }
+}
+
+impl Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}[{}] {} ({}): {}",
+            self.severity, self.code, self.mod_name, self.field, self.message
+        )
+    }
+}
+
+/// Issues found in a modlinks, ordered by mod name then by when they were
+/// found.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct ValidationReport {
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl Extend<ValidationIssue> for ValidationReport {
+    fn extend<T: IntoIterator<Item = ValidationIssue>>(&mut self, iter: T) {
+        self.issues.extend(iter);
+    }
+}
+
+impl IntoIterator for ValidationReport {
+    type IntoIter = std::vec::IntoIter<ValidationIssue>;
+    type Item = <Self::IntoIter as Iterator>::Item;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.issues.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a ValidationReport {
+    type IntoIter = std::slice::Iter<'a, ValidationIssue>;
+    type Item = <Self::IntoIter as Iterator>::Item;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.issues.iter()
+    }
+}
+
+impl ValidationReport {
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    #[inline]
+    pub fn push(&mut self, issue: ValidationIssue) {
+        self.issues.push(issue);
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    #[inline]
+    pub fn iter(&self) -> std::slice::Iter<'_, ValidationIssue> {
+        self.issues.iter()
+    }
+
+    #[must_use]
+    pub fn count(&self, severity: Severity) -> usize {
+        self.issues
+            .iter()
+            .filter(|i| i.severity == severity)
+            .count()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn has_errors(&self) -> bool {
+        self.issues.iter().any(|i| i.severity == Severity::Error)
+    }
+
+    pub fn issues_for<'a>(
+        &'a self,
+        mod_name: &'a str,
+    ) -> impl Iterator<Item = &'a ValidationIssue> {
+        self.issues.iter().filter(move |i| i.mod_name == mod_name)
+    }
+
+    /// Stable sort issues by mod name.
+    pub fn sort(&mut self) {
+        self.issues.sort_by(|a, b| a.mod_name.cmp(&b.mod_name));
+    }
+}
+
+impl ModLinks {
+    /// Check names, relations, dependency cycles and file URLs of all mods in
+    /// one pass. Hashes are not verified, as that requires downloading.
+    #[must_use]
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::new();
+
+        for (name, info) in self {
+            if !is_valid_mod_name(name) {
+                report.push(ValidationIssue::error(
+                    "invalid-name",
+                    name,
+                    "name",
+                    "Invalid mod name",
+                ));
+            }
+
+            for dep in info.dependencies.iter().filter(|i| !self.contains(i)) {
+                report.push(ValidationIssue::error(
+                    "unknown-dependency",
+                    name,
+                    "dependencies",
+                    format!("Unknown mod {dep}"),
+                ));
+            }
+
+            for integration in info.integrations.iter().filter(|i| !self.contains(i)) {
+                report.push(ValidationIssue::error(
+                    "unknown-integration",
+                    name,
+                    "integrations",
+                    format!("Unknown mod {integration}"),
+                ));
+            }
+
+            for (platform, file) in info.links.files() {
+                if !is_valid_file_url(&file.url) {
+                    report.push(ValidationIssue::error(
+                        "invalid-file-url",
+                        name,
+                        links_field(platform),
+                        format!("URL {} is not http or https", file.url),
+                    ));
+                }
+            }
+        }
+
+        if let Err(cycles) = self.validate_acyclic() {
+            for cycle in cycles {
+                report.push(ValidationIssue::error(
+                    "dependency-cycle",
+                    cycle[0],
+                    "dependencies",
+                    format!("Dependency cycle {} -> {}", cycle.join(" -> "), cycle[0]),
+                ));
+            }
+        }
+
+        report.sort();
+        report
+    }
+}
+
+/// Field name of the file for the platform in [`ValidationIssue::field`].
+#[must_use]
+pub fn links_field(platform: Option<crate::Platform>) -> String {
+    match platform {
+        Some(platform) => format!("links.{}", platform.to_string().to_lowercase()),
+        None => "links".to_string(),
+    }
+}
+
+#[cfg(feature = "report")]
+impl ValidationReport {
+    /// JUnit XML with a test case for each of the mods, failing when it has
+    /// errors. Warnings and info are written to the output of the test case.
+    pub fn to_junit<'a>(
+        &self,
+        suite_name: &str,
+        mods: impl IntoIterator<Item = &'a str>,
+    ) -> Result<String, quick_xml::Error> {
+        use quick_xml::events::{BytesDecl, BytesText, Event};
+        use quick_xml::Writer;
+
+        let mods: Vec<&str> = mods.into_iter().collect();
+        let failures = mods
+            .iter()
+            .filter(|name| self.issues_for(name).any(|i| i.severity == Severity::Error))
+            .count();
+
+        let mut writer = Writer::new_with_indent(Vec::new(), b'\t', 1);
+        writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("utf-8"), None)))?;
+
+        writer
+            .create_element("testsuite")
+            .with_attributes([
+                ("name", suite_name),
+                ("tests", &mods.len().to_string()),
+                ("failures", &failures.to_string()),
+                ("errors", "0"),
+            ])
+            .write_inner_content(|writer| {
+                for &name in &mods {
+                    let (errors, others): (Vec<_>, Vec<_>) = self
+                        .issues_for(name)
+                        .partition(|i| i.severity == Severity::Error);
+
+                    let testcase = writer
+                        .create_element("testcase")
+                        .with_attributes([("classname", suite_name), ("name", name)]);
+
+                    if errors.is_empty() && others.is_empty() {
+                        testcase.write_empty()?;
+                        continue;
+                    }
+
+                    testcase.write_inner_content(|writer| {
+                        if let Some(first) = errors.first() {
+                            let message = format!("{}: {}", first.field, first.message);
+                            let body = errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+                            writer
+                                .create_element("failure")
+                                .with_attributes([
+                                    ("type", first.code.as_str()),
+                                    ("message", message.as_str()),
+                                ])
+                                .write_text_content(BytesText::new(&body.join("\n")))?;
+                        }
+
+                        if !others.is_empty() {
+                            let body = others.iter().map(ToString::to_string).collect::<Vec<_>>();
+                            writer
+                                .create_element("system-out")
+                                .write_text_content(BytesText::new(&body.join("\n")))?;
+                        }
+
+                        Ok::<_, quick_xml::Error>(())
+                    })?;
+                }
+
+                Ok::<_, quick_xml::Error>(())
+            })?;
+
+        Ok(String::from_utf8(writer.into_inner()).expect("JUnit report should be valid utf-8"))
+    }
+
+    /// SARIF 2.1.0 log with a result for each issue, located by mod and field.
+    /// The artifact is the modlinks file, if known.
+    #[must_use]
+    pub fn to_sarif(&self, tool_name: &str, artifact_uri: Option<&str>) -> serde_json::Value {
+        use serde_json::json;
+
+        let rules: std::collections::BTreeSet<&str> =
+            self.issues.iter().map(|i| i.code.as_str()).collect();
+
+        let results = self
+            .issues
+            .iter()
+            .map(|issue| {
+                let level = match issue.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                    Severity::Info => "note",
+                };
+
+                let mut location = json!({
+                    "logicalLocations": [{
+                        "name": issue.field,
+                        "fullyQualifiedName": format!("{}.{}", issue.mod_name, issue.field),
+                        "kind": "member",
+                    }],
+                });
+                if let Some(uri) = artifact_uri {
+                    location["physicalLocation"] = json!({ "artifactLocation": { "uri": uri } });
+                }
+
+                json!({
+                    "ruleId": issue.code,
+                    "level": level,
+                    "message": { "text": format!("{}: {}", issue.mod_name, issue.message) },
+                    "locations": [location],
+                })
+            })
+            .collect::<Vec<_>>();
+
+        json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": tool_name,
+                        "rules": rules.into_iter().map(|id| json!({ "id": id })).collect::<Vec<_>>(),
+                    },
+                },
+                "results": results,
+            }],
+        })
+    }
+
+    /// Issue counts by severity along with all issues.
+    #[must_use]
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "errors": self.count(Severity::Error),
+            "warnings": self.count(Severity::Warning),
+            "info": self.count(Severity::Info),
+            "issues": self,
+        })
+    }
+}
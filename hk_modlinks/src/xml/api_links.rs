@@ -25,7 +25,7 @@ pub struct ApiLinks<'a> {
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "PascalCase")]
 struct Manifest<'a> {
-    version: Cow<'a, String>,
+    version: Cow<'a, str>,
     #[serde(flatten)]
     links: Links<'a>,
     files: FileList<'a>,
@@ -47,7 +47,7 @@ impl<'a> From<&'a crate::ApiLinks> for ApiLinks<'a> {
     fn from(value: &'a crate::ApiLinks) -> Self {
         Self {
             manifest: Manifest {
-                version: Cow::Borrowed(&value.version),
+                version: Cow::Borrowed(value.version.as_str()),
                 links: (&value.links).into(),
                 files: (&value.files).into(),
             },
@@ -13,9 +13,9 @@ use crate::Version;
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename = "Manifest", rename_all = "PascalCase")]
 pub struct ModInfo<'a> {
-    name: Cow<'a, String>,
-    display_name: Option<Cow<'a, String>>,
-    description: Cow<'a, String>,
+    name: Cow<'a, str>,
+    display_name: Option<Cow<'a, str>>,
+    description: Cow<'a, str>,
     version: Cow<'a, Version>,
     #[serde(flatten)]
     links: Links<'a>,
@@ -69,9 +69,9 @@ impl<'a> From<ModInfo<'a>> for (String, crate::ModInfo) {
 impl<'a> From<(&'a String, &'a crate::ModInfo)> for ModInfo<'a> {
     fn from((name, value): (&'a String, &'a crate::ModInfo)) -> Self {
         Self {
-            name: Cow::Borrowed(name),
-            display_name: value.display_name.as_ref().map(Cow::Borrowed),
-            description: Cow::Borrowed(&value.description),
+            name: Cow::Borrowed(name.as_str()),
+            display_name: value.display_name.as_deref().map(Cow::Borrowed),
+            description: Cow::Borrowed(value.description.as_str()),
             version: Cow::Borrowed(&value.version),
             links: (&value.links).into(),
             dependencies: (&value.dependencies).into(),
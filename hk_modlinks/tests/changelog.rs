@@ -0,0 +1,581 @@
+use std::fs;
+use std::path::PathBuf;
+
+use handlebars::Handlebars;
+
+use serde_json::{json, Value};
+
+use hk_modlinks::{
+    atom_entry_id, atom_entry_timestamps, AtomFeedOptions, ChangelogRenderer, FieldChange,
+    ModEvent, ModLinks, ModLinksHistory, Tag, TimelineEntry, Version, ATOM_DEFAULT_AUTHOR,
+    CHANGELOG_JSON_SCHEMA, CHANGELOG_TEMPLATE_MARKDOWN, MARKDOWN_PARTIALS,
+};
+
+/// Set this environment variable to rewrite golden files with current output.
+const BLESS_ENV: &str = "HK_MODLINKS_BLESS";
+
+fn fixture_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/changelog")
+        .join(name)
+}
+
+fn read_mod_links(name: &str) -> ModLinks {
+    let xml = fs::read_to_string(fixture_path(name)).unwrap();
+    ModLinks::from_xml(&xml).unwrap()
+}
+
+fn assert_golden(name: &str, actual: &str) {
+    let path = fixture_path(name);
+
+    if std::env::var_os(BLESS_ENV).is_some() {
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!("Failed to read golden file {path:?} ({e}), run with {BLESS_ENV}=1 to create it")
+    });
+
+    assert!(
+        expected == actual,
+        "Output does not match golden file {path:?}, run with {BLESS_ENV}=1 to update it\n\
+        --- expected ---\n{expected}\n--- actual ---\n{actual}"
+    );
+}
+
+#[test]
+fn markdown_matches_golden() {
+    let old = read_mod_links("old.xml");
+    let new = read_mod_links("new.xml");
+
+    assert_golden(
+        "expected.md",
+        &new.changelog_since(&old).to_markdown().unwrap(),
+    );
+}
+
+#[test]
+fn markdown_template_renders_with_exported_partials() {
+    let old = read_mod_links("old.xml");
+    let new = read_mod_links("new.xml");
+    let changelog = new.changelog_since(&old);
+
+    let mut registry = Handlebars::new();
+    registry.set_strict_mode(true);
+    for (name, partial) in MARKDOWN_PARTIALS {
+        registry.register_partial(name, partial).unwrap();
+    }
+    registry
+        .register_template_string("changelog", CHANGELOG_TEMPLATE_MARKDOWN)
+        .unwrap();
+
+    assert_eq!(
+        registry.render("changelog", &changelog).unwrap(),
+        changelog.to_markdown().unwrap()
+    );
+}
+
+#[test]
+fn html_matches_golden() {
+    let old = read_mod_links("old.xml");
+    let new = read_mod_links("new.xml");
+
+    assert_golden(
+        "expected.html",
+        &new.changelog_since(&old).to_html().unwrap(),
+    );
+}
+
+#[test]
+fn plain_matches_golden() {
+    let old = read_mod_links("old.xml");
+    let new = read_mod_links("new.xml");
+
+    assert_golden(
+        "expected.txt",
+        &new.changelog_since(&old).to_plain().unwrap(),
+    );
+}
+
+#[test]
+fn json_matches_golden() {
+    let old = read_mod_links("old.xml");
+    let new = read_mod_links("new.xml");
+
+    let mut json = new.changelog_since(&old).to_json().unwrap();
+    json.push('\n');
+
+    assert_golden("expected.json", &json);
+}
+
+/// Check `value` against the subset of JSON Schema that
+/// [`CHANGELOG_JSON_SCHEMA`] uses, panicking on keywords it does not know.
+fn check_schema(root: &Value, schema: &Value, value: &Value, path: &str) -> Result<(), String> {
+    let err = |what: &str| Err(format!("{path}: {what}, got {value}"));
+    // Keywords for objects or arrays do not apply to other values
+    let empty = serde_json::Map::new();
+    let object = value.as_object().unwrap_or(&empty);
+    let array = value.as_array().map_or(&[][..], Vec::as_slice);
+
+    for (keyword, arg) in schema.as_object().unwrap() {
+        match keyword.as_str() {
+            "$schema" | "$id" | "$defs" | "title" | "description" | "format" => {}
+            "$ref" => {
+                let name = arg.as_str().unwrap().strip_prefix("#/$defs/").unwrap();
+                check_schema(root, &root["$defs"][name], value, path)?;
+            }
+            "type" => {
+                let types = match arg {
+                    Value::Array(types) => types.iter().map(|t| t.as_str().unwrap()).collect(),
+                    t => vec![t.as_str().unwrap()],
+                };
+                let actual = match value {
+                    Value::Null => "null",
+                    Value::Bool(_) => "boolean",
+                    Value::Number(_) => "number",
+                    Value::String(_) => "string",
+                    Value::Array(_) => "array",
+                    Value::Object(_) => "object",
+                };
+                if !types.contains(&actual) {
+                    return err(&format!("expected {types:?}"));
+                }
+            }
+            "enum" => {
+                if !arg.as_array().unwrap().contains(value) {
+                    return err(&format!("expected one of {arg}"));
+                }
+            }
+            "oneOf" => {
+                let matching = arg
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .filter(|schema| check_schema(root, schema, value, path).is_ok())
+                    .count();
+                if matching != 1 {
+                    return err(&format!("{matching} schemas of oneOf match"));
+                }
+            }
+            "required" => {
+                for key in arg.as_array().unwrap() {
+                    if value.is_object() && !object.contains_key(key.as_str().unwrap()) {
+                        return err(&format!("missing {key}"));
+                    }
+                }
+            }
+            "minProperties" => {
+                if value.is_object() && object.len() < arg.as_u64().unwrap() as usize {
+                    return err(&format!("expected at least {arg} properties"));
+                }
+            }
+            "properties" => {
+                for (key, schema) in arg.as_object().unwrap() {
+                    if let Some(value) = object.get(key) {
+                        check_schema(root, schema, value, &format!("{path}/{key}"))?;
+                    }
+                }
+            }
+            "additionalProperties" => {
+                let known = schema.get("properties").and_then(Value::as_object);
+                for (key, value) in object {
+                    if known.is_none_or(|known| !known.contains_key(key)) {
+                        check_schema(root, arg, value, &format!("{path}/{key}"))?;
+                    }
+                }
+            }
+            "propertyNames" => {
+                for key in object.keys() {
+                    check_schema(root, arg, &json!(key), &format!("{path}/{key}"))?;
+                }
+            }
+            "items" => {
+                for (i, item) in array.iter().enumerate() {
+                    check_schema(root, arg, item, &format!("{path}/{i}"))?;
+                }
+            }
+            "uniqueItems" => {
+                if array
+                    .iter()
+                    .enumerate()
+                    .any(|(i, item)| array[..i].contains(item))
+                {
+                    return err("expected unique items");
+                }
+            }
+            keyword => panic!("Unsupported keyword {keyword} at {path}"),
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn json_follows_schema() {
+    let old = read_mod_links("old.xml");
+    let new = read_mod_links("new.xml");
+    let schema: Value = serde_json::from_str(CHANGELOG_JSON_SCHEMA).unwrap();
+
+    for changelog in [
+        new.changelog_since(&old),
+        new.changelog_until(&old),
+        old.changelog_since(&old),
+    ] {
+        check_schema(&schema, &schema, changelog.json(), "").unwrap();
+    }
+
+    let mut broken = new.changelog_since(&old).json().clone();
+    broken["updated"]["Bumped"]["links"]["kind"] = json!("universal");
+    assert!(check_schema(&schema, &schema, &broken, "").is_err());
+}
+
+fn atom_options() -> AtomFeedOptions {
+    AtomFeedOptions::builder()
+        .id("urn:hk-modlinks:test")
+        .title("Test feed")
+        .updated("2024-01-01T00:00:00Z")
+        .author("hkml")
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn atom_matches_golden() {
+    let old = read_mod_links("old.xml");
+    let new = read_mod_links("new.xml");
+
+    assert_golden(
+        "expected.atom",
+        &new.atom_since(&old, &atom_options()).unwrap(),
+    );
+}
+
+#[test]
+fn atom_entry_ids_are_stable() {
+    let old = read_mod_links("old.xml");
+    let new = read_mod_links("new.xml");
+
+    let id = atom_entry_id("Brand New", &new["Brand New"].version);
+    assert!(id.starts_with("urn:hk-modlinks:Brand+New:"));
+
+    let first = new.atom_since(&old, &atom_options()).unwrap();
+    let second = new.atom_since(&old, &atom_options()).unwrap();
+    assert_eq!(first, second);
+    assert!(first.contains(&format!("<id>{id}</id>")));
+    assert!(!first.contains(&atom_entry_id("Unchanged", &new["Unchanged"].version)));
+}
+
+#[test]
+fn atom_entries_keep_published_timestamps() {
+    let old = read_mod_links("old.xml");
+    let new = read_mod_links("new.xml");
+    let first = new.atom_since(&old, &atom_options()).unwrap();
+
+    let mut options = atom_options();
+    options.updated = "2024-02-01T00:00:00Z".to_string();
+    options.entry_updated = atom_entry_timestamps(&first).unwrap();
+    let brand_new = atom_entry_id("Brand New", &new["Brand New"].version);
+    options.entry_updated.remove(&brand_new);
+
+    let second = new.atom_since(&old, &options).unwrap();
+    let published = atom_entry_timestamps(&second).unwrap();
+    assert_eq!(published[&brand_new], "2024-02-01T00:00:00Z");
+    assert_eq!(
+        published
+            .iter()
+            .filter(|(_, updated)| *updated == "2024-01-01T00:00:00Z")
+            .count(),
+        published.len() - 1
+    );
+    assert!(second.contains("<updated>2024-02-01T00:00:00Z</updated>\n\t<author>"));
+}
+
+#[test]
+fn atom_falls_back_to_default_author() {
+    let old = read_mod_links("old.xml");
+    let new = read_mod_links("new.xml");
+
+    let mut options = atom_options();
+    options.author = None;
+
+    // Modern has no authors, so the feed needs one
+    assert!(new["Modern"].authors.is_empty());
+    let feed = new.atom_since(&old, &options).unwrap();
+    assert!(feed.contains(&format!(
+        "<updated>2024-01-01T00:00:00Z</updated>\n\t<author>\n\t\t<name>{ATOM_DEFAULT_AUTHOR}</name>"
+    )));
+
+    let feed = new.atom_since(&new, &options).unwrap();
+    assert!(!feed.contains("<author>"));
+}
+
+#[test]
+fn reverse_markdown_matches_golden() {
+    let old = read_mod_links("old.xml");
+    let new = read_mod_links("new.xml");
+
+    assert_golden(
+        "expected-reverse.md",
+        &new.changelog_until(&old).to_markdown().unwrap(),
+    );
+}
+
+#[test]
+fn identical_mod_links_have_empty_changelog() {
+    let mod_links = read_mod_links("old.xml");
+    let changelog = mod_links.changelog_since(&mod_links);
+
+    assert_eq!(
+        changelog.json(),
+        &json!({
+            "new": null,
+            "removed": [],
+            "updated": null,
+            "changed": null
+        })
+    );
+    assert_eq!(changelog.to_markdown().unwrap().trim(), "# Changelog");
+}
+
+#[test]
+fn tags_are_not_inverted() {
+    let old = read_mod_links("old.xml");
+    let new = read_mod_links("new.xml");
+    let changelog = new.changelog_since(&old);
+
+    assert_eq!(
+        changelog.json()["updated"]["Bumped"]["tags"],
+        json!({
+            "removed": ["Boss"],
+            "added": ["Gameplay"]
+        })
+    );
+}
+
+#[test]
+fn set_fields_agree_on_direction() {
+    let old = read_mod_links("old.xml");
+    let new = read_mod_links("new.xml");
+    let changelog = new.changelog_since(&old);
+    let bumped = &changelog.json()["updated"]["Bumped"];
+
+    assert_eq!(
+        bumped["dependencies"],
+        json!({ "removed": ["Legacy"], "added": ["Modern"] })
+    );
+    assert_eq!(
+        bumped["integrations"],
+        json!({ "removed": ["Extra"], "added": ["Core"] })
+    );
+    assert_eq!(
+        bumped["authors"],
+        json!({ "removed": ["Bob"], "added": ["Dave"] })
+    );
+}
+
+#[test]
+fn changes_without_version_bump_are_reported() {
+    let old = read_mod_links("old.xml");
+    let new = read_mod_links("new.xml");
+    let changelog = new.changelog_since(&old);
+    let json = changelog.json();
+
+    let changed = json["changed"].as_object().unwrap();
+    assert_eq!(changed.keys().collect::<Vec<_>>(), ["Reuploaded", "Split"]);
+    assert_eq!(changed["Reuploaded"]["version"], json!(null));
+    assert!(json["updated"].get("Reuploaded").is_none());
+
+    let markdown = changelog.to_markdown().unwrap();
+    assert!(markdown.contains("## Changed without version bump"));
+}
+
+#[test]
+fn links_kind_changes_list_new_files() {
+    let old = read_mod_links("old.xml");
+    let new = read_mod_links("new.xml");
+    let changelog = new.changelog_since(&old);
+    let json = changelog.json();
+
+    let split = &json["changed"]["Split"]["links"];
+    assert_eq!(
+        split["kind"],
+        json!({ "old": "universal", "new": "platform-specific" })
+    );
+    assert_eq!(
+        split["files"]["Windows"],
+        json!({
+            "sha256": { "old": null, "new": "6".repeat(64) },
+            "url": { "old": null, "new": "https://example.com/split-win.zip" }
+        })
+    );
+    assert_eq!(
+        split["files"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .collect::<Vec<_>>(),
+        ["Linux", "Mac", "Windows"]
+    );
+
+    let merged = &json["updated"]["Merged"]["links"];
+    assert_eq!(
+        merged["kind"],
+        json!({ "old": "platform-specific", "new": "universal" })
+    );
+    assert_eq!(
+        merged["files"],
+        json!({
+            "Universal": {
+                "sha256": { "old": null, "new": "2".repeat(64) },
+                "url": { "old": null, "new": "https://example.com/merged.zip" }
+            }
+        })
+    );
+}
+#[test]
+fn typed_diff_classifies_updates() {
+    let old = read_mod_links("old.xml");
+    let new = read_mod_links("new.xml");
+    let diff = new.diff_since(&old);
+
+    assert_eq!(
+        diff.added.keys().copied().collect::<Vec<_>>(),
+        ["Brand New", "Modern"]
+    );
+    assert_eq!(diff.removed.keys().copied().collect::<Vec<_>>(), ["Legacy"]);
+    assert_eq!(
+        diff.version_bumps()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>(),
+        ["Bumped", "Merged", "Windowed"]
+    );
+    assert_eq!(
+        diff.unversioned_changes()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>(),
+        ["Reuploaded", "Split"]
+    );
+    assert!(!diff.updated.contains_key("Unchanged"));
+
+    assert!(diff.updated["Reuploaded"].is_sha256_only());
+    assert!(!diff.updated["Split"].is_sha256_only());
+    assert!(!diff.updated["Bumped"].is_sha256_only());
+}
+
+#[test]
+fn typed_diff_keeps_set_direction() {
+    let old = read_mod_links("old.xml");
+    let new = read_mod_links("new.xml");
+    let diff = new.diff_since(&old);
+
+    let tags = diff.updated["Bumped"].tags.unwrap();
+    assert_eq!(tags.removed().copied().collect::<Vec<_>>(), [Tag::Boss]);
+    assert_eq!(tags.added().copied().collect::<Vec<_>>(), [Tag::Gameplay]);
+
+    let dependencies = diff.updated["Bumped"].dependencies.unwrap();
+    assert_eq!(dependencies.removed().collect::<Vec<_>>(), ["Legacy"]);
+    assert_eq!(dependencies.added().collect::<Vec<_>>(), ["Modern"]);
+}
+
+#[test]
+fn custom_template_with_partial() {
+    let old = read_mod_links("old.xml");
+    let new = read_mod_links("new.xml");
+    let changelog = new.changelog_since(&old);
+
+    let mut renderer = ChangelogRenderer::new();
+    renderer
+        .register_partial("version", "{{this.version.old}} => {{this.version.new}}")
+        .unwrap();
+    renderer
+        .register_template(
+            "list",
+            "{{#each updated}}{{@key}}: {{> version}}\n{{/each}}",
+        )
+        .unwrap();
+
+    assert_eq!(
+        renderer.render("list", &changelog).unwrap(),
+        "Bumped: 1.0.0.0 => 1.1.0.0\n\
+        Merged: 2.0.0.0 => 2.1.0.0\n\
+        Windowed: 1.0.0.0 => 1.0.1.0\n"
+    );
+    assert_eq!(changelog.render_with("{{removed.[0]}}").unwrap(), "Legacy");
+}
+
+#[test]
+fn custom_template_is_strict() {
+    let old = read_mod_links("old.xml");
+    let new = read_mod_links("new.xml");
+    let changelog = new.changelog_since(&old);
+
+    let mut renderer = ChangelogRenderer::new();
+    renderer
+        .register_template("broken", "{{#each updated}}\n{{this.missing}}\n{{/each}}")
+        .unwrap();
+
+    let err = renderer.render("broken", &changelog).unwrap_err();
+    assert_eq!(err.template_name.as_deref(), Some("broken"));
+    assert_eq!(err.line_no, Some(2));
+}
+
+#[test]
+fn history_timelines() {
+    let old = read_mod_links("old.xml");
+    let new = read_mod_links("new.xml");
+    let history = ModLinksHistory::new([("v1", &old), ("v2", &new), ("v3", &old)]);
+
+    assert_eq!(history.steps.len(), 2);
+    assert_eq!(history.steps[0].previous, "v1");
+    assert_eq!(history.steps[1].snapshot, "v3");
+    assert_eq!(history.steps[0].diff, new.diff_since(&old));
+
+    let v1 = Version::new(1, 0, 0, 0);
+    let v1_1 = Version::new(1, 1, 0, 0);
+    assert_eq!(
+        history.timeline("Bumped").unwrap(),
+        [
+            TimelineEntry {
+                snapshot: "v1",
+                event: ModEvent::Initial { version: &v1 },
+            },
+            TimelineEntry {
+                snapshot: "v2",
+                event: ModEvent::Updated {
+                    version: FieldChange::new(&v1, &v1_1),
+                },
+            },
+            TimelineEntry {
+                snapshot: "v3",
+                event: ModEvent::Updated {
+                    version: FieldChange::new(&v1_1, &v1),
+                },
+            },
+        ]
+    );
+    assert_eq!(
+        history.timeline("Legacy").unwrap(),
+        [
+            TimelineEntry {
+                snapshot: "v1",
+                event: ModEvent::Initial { version: &v1 },
+            },
+            TimelineEntry {
+                snapshot: "v2",
+                event: ModEvent::Removed,
+            },
+            TimelineEntry {
+                snapshot: "v3",
+                event: ModEvent::Added { version: &v1 },
+            },
+        ]
+    );
+    assert_eq!(history.timeline("Unchanged").unwrap().len(), 1);
+
+    let markdown = history.changelog().to_markdown().unwrap();
+    assert!(markdown.contains("## v2\n\nChanges since v1."));
+    assert!(markdown.contains(
+        "## Legacy\n\n- v1: present at 1.0.0.0\n- v2: removed\n- v3: added at 1.0.0.0\n"
+    ));
+}
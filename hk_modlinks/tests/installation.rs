@@ -0,0 +1,152 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+
+use hk_modlinks::{Installation, InstalledMod, ModLinks, ModUpdate, Version};
+
+fn read_mod_links() -> ModLinks {
+    let path =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/relations/modlinks.xml");
+    ModLinks::from_xml(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+fn installed(mod_links: &ModLinks, name: &str, version: &str) -> (String, InstalledMod) {
+    let info = &mod_links[name];
+    let (_, file) = info.links.files()[0];
+
+    (
+        name.to_string(),
+        InstalledMod {
+            version: version.parse().unwrap(),
+            ..InstalledMod::new(info, file)
+        },
+    )
+}
+
+fn version(s: &str) -> Version {
+    s.parse().unwrap()
+}
+
+#[test]
+fn outdated_mods_and_new_dependencies() {
+    let mod_links = read_mod_links();
+
+    let installation = [
+        // Installed before it depended on Ui
+        (
+            "Boss".to_string(),
+            InstalledMod {
+                dependencies: BTreeSet::from(["Api".to_string()]),
+                ..installed(&mod_links, "Boss", "0.9.0.0").1
+            },
+        ),
+        installed(&mod_links, "Api", "0.1.0.0"),
+        installed(&mod_links, "Core", "1.0.0.0"),
+        // Newer than the modlinks
+        installed(&mod_links, "Standalone", "2.0.0.0"),
+        (
+            "Gone".to_string(),
+            InstalledMod {
+                version: version("1.0.0.0"),
+                sha256: [0; 32],
+                dependencies: BTreeSet::new(),
+            },
+        ),
+    ]
+    .into_iter()
+    .collect::<Installation>();
+
+    let outdated = installation.outdated(&mod_links);
+    let (old_api, old_boss) = (version("0.1.0.0"), version("0.9.0.0"));
+    let current = version("1.0.0.0");
+    assert_eq!(
+        outdated.updates,
+        [
+            ModUpdate {
+                name: "Api",
+                installed: &old_api,
+                available: &current,
+            },
+            ModUpdate {
+                name: "Boss",
+                installed: &old_boss,
+                available: &current,
+            },
+        ]
+    );
+    assert_eq!(outdated.new_dependencies, ["Ui"]);
+    assert_eq!(outdated.missing, ["Gone"]);
+    assert!(outdated.missing_dependencies.is_empty());
+    assert!(!outdated.is_empty());
+
+    let up_to_date = [
+        installed(&mod_links, "Core", "1.0.0.0"),
+        installed(&mod_links, "Extra", "1.0.0.0"),
+    ]
+    .into_iter()
+    .collect::<Installation>();
+    assert!(up_to_date.outdated(&mod_links).is_empty());
+}
+
+#[test]
+fn outdated_reports_missing_dependencies_per_mod() {
+    let mut mod_links = read_mod_links();
+    let installation = [
+        installed(&mod_links, "Boss", "0.9.0.0"),
+        installed(&mod_links, "Api", "0.1.0.0"),
+        installed(&mod_links, "Core", "1.0.0.0"),
+        installed(&mod_links, "Ui", "1.0.0.0"),
+    ]
+    .into_iter()
+    .collect::<Installation>();
+    mod_links.remove("Ui");
+
+    let outdated = installation.outdated(&mod_links);
+    let (old_api, current) = (version("0.1.0.0"), version("1.0.0.0"));
+    assert_eq!(
+        outdated.updates,
+        [ModUpdate {
+            name: "Api",
+            installed: &old_api,
+            available: &current,
+        }]
+    );
+    assert!(outdated.new_dependencies.is_empty());
+    assert_eq!(outdated.missing, ["Ui"]);
+    assert_eq!(
+        outdated
+            .missing_dependencies
+            .iter()
+            .map(|missing| missing.chain.clone())
+            .collect::<Vec<_>>(),
+        [vec!["Boss", "Ui"]]
+    );
+}
+
+#[test]
+fn installation_round_trip() {
+    let mod_links = read_mod_links();
+    let installation = [
+        installed(&mod_links, "Api", "1.0.0.0"),
+        installed(&mod_links, "Core", "1.0.0.0"),
+    ]
+    .into_iter()
+    .collect::<Installation>();
+
+    assert_eq!(installation.dependents("Core").collect::<Vec<_>>(), ["Api"]);
+    assert_eq!(installation.dependents("Api").count(), 0);
+
+    let json = serde_json::to_value(&installation).unwrap();
+    assert_eq!(json["Api"]["version"], "1.0.0.0");
+    assert_eq!(
+        json["Api"]["sha256"],
+        installation.get("Api").unwrap().sha256()
+    );
+    assert_eq!(json["Api"]["dependencies"], serde_json::json!(["Core"]));
+    assert!(json["Core"].get("dependencies").is_none());
+
+    assert_eq!(
+        serde_json::from_value::<Installation>(json).unwrap(),
+        installation
+    );
+}
@@ -0,0 +1,331 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+
+use hk_modlinks::{Dependents, GraphOptions, ModLinks, ResolveOptions, SkipReason, Tag};
+
+fn read_mod_links() -> ModLinks {
+    let path =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/relations/modlinks.xml");
+    ModLinks::from_xml(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+fn dependents<'a>(required_by: &[&'a str], integrated_by: &[&'a str]) -> Dependents<'a> {
+    Dependents {
+        required_by: required_by.iter().copied().collect::<BTreeSet<_>>(),
+        integrated_by: integrated_by.iter().copied().collect::<BTreeSet<_>>(),
+    }
+}
+
+#[test]
+fn direct_dependents() {
+    let mod_links = read_mod_links();
+
+    assert_eq!(
+        mod_links.dependents("Core"),
+        dependents(&["Api", "Extra", "Ui"], &[])
+    );
+    assert_eq!(
+        mod_links.dependents("Api"),
+        dependents(&["Boss"], &["Addon"])
+    );
+    assert_eq!(
+        mod_links.dependents("Ui"),
+        dependents(&["Boss"], &["Fancy"])
+    );
+    assert!(mod_links.dependents("Standalone").is_empty());
+    assert!(mod_links.dependents("Unknown").is_empty());
+}
+
+#[test]
+fn transitive_dependents() {
+    let mod_links = read_mod_links();
+
+    assert_eq!(
+        mod_links.dependents_transitive("Core"),
+        dependents(&["Addon", "Api", "Boss", "Extra", "Ui"], &["Fancy"])
+    );
+    assert_eq!(
+        mod_links.dependents_transitive("Api"),
+        dependents(&["Addon", "Boss"], &["Extra"])
+    );
+    assert_eq!(
+        mod_links.dependents_transitive("Addon"),
+        dependents(&[], &[])
+    );
+}
+
+#[test]
+fn install_plan_puts_dependencies_first() {
+    let mod_links = read_mod_links();
+
+    assert_eq!(
+        mod_links.install_plan(["Addon"]).unwrap().as_slice(),
+        ["Core", "Api", "Ui", "Boss", "Addon"]
+    );
+    assert_eq!(
+        mod_links
+            .install_plan(["Standalone", "Extra", "Fancy"])
+            .unwrap()
+            .as_slice(),
+        ["Core", "Extra", "Fancy", "Standalone"]
+    );
+    assert!(mod_links
+        .install_plan(["Nope"])
+        .unwrap_err()
+        .names()
+        .eq(["Nope"]));
+}
+
+#[test]
+fn install_plan_is_deterministic() {
+    let mod_links = read_mod_links();
+    let names = mod_links
+        .mod_names()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+
+    let forward = mod_links.install_plan(names.iter().copied()).unwrap();
+    let backward = mod_links.install_plan(names.iter().rev().copied()).unwrap();
+
+    assert_eq!(forward, backward);
+    assert_eq!(
+        forward.as_slice(),
+        [
+            "Core",
+            "Api",
+            "Extra",
+            "Fancy",
+            "Standalone",
+            "Ui",
+            "Boss",
+            "Addon"
+        ]
+    );
+}
+
+#[test]
+fn install_plan_breaks_cycles_by_name() {
+    let mut mod_links = read_mod_links();
+    mod_links["Core"].dependencies.insert("Boss".to_string());
+
+    assert_eq!(
+        mod_links.install_plan(["Addon"]).unwrap().as_slice(),
+        ["Api", "Boss", "Addon", "Core", "Ui"]
+    );
+}
+
+#[test]
+fn acyclic_mod_links_validate() {
+    let mod_links = read_mod_links();
+
+    // Integration cycles are allowed
+    assert!(mod_links["Extra"].integrations.contains("Boss"));
+    assert_eq!(mod_links.validate_acyclic(), Ok(()));
+}
+
+#[test]
+fn cycles_are_reported_as_paths() {
+    let mut mod_links = read_mod_links();
+    mod_links["Core"].dependencies.insert("Boss".to_string());
+    mod_links["Standalone"]
+        .dependencies
+        .insert("Standalone".to_string());
+    mod_links["Fancy"].dependencies.insert("Extra".to_string());
+
+    assert_eq!(
+        mod_links.validate_acyclic(),
+        Err(vec![vec!["Api", "Core", "Boss"], vec!["Standalone"]])
+    );
+}
+
+#[test]
+fn graph_distinguishes_edge_kinds() {
+    let mod_links = read_mod_links();
+    let options = GraphOptions::builder().root("Extra").build().unwrap();
+
+    assert_eq!(
+        mod_links.to_dot(&options).unwrap(),
+        "digraph modlinks {\n\
+        \tnode [shape=box];\n\
+        \t\"Boss\" [label=\"Boss\"];\n\
+        \t\"Core\" [label=\"Core\"];\n\
+        \t\"Extra\" [label=\"Extra\"];\n\
+        \t\"Extra\" -> \"Boss\" [style=dashed];\n\
+        \t\"Extra\" -> \"Core\";\n\
+        }\n"
+    );
+
+    let mermaid = mod_links.to_mermaid(&options).unwrap();
+    assert!(mermaid.contains("\tn2 -.-> n0\n"));
+    assert!(mermaid.contains("\tn2 --> n1\n"));
+}
+
+#[test]
+fn graph_colors_by_tag_and_rejects_unknown_roots() {
+    let mut mod_links = read_mod_links();
+    mod_links["Core"].tags.insert(Tag::Library);
+
+    let options = GraphOptions::builder()
+        .root("Api")
+        .color_by_tag(true)
+        .build()
+        .unwrap();
+    assert!(mod_links
+        .to_dot(&options)
+        .unwrap()
+        .contains("\t\"Core\" [label=\"Core\", style=filled, fillcolor=\"#d9d9d9\"];\n"));
+
+    let options = GraphOptions::builder().root("Nope").build().unwrap();
+    assert!(mod_links.to_dot(&options).unwrap_err().names().eq(["Nope"]));
+}
+
+#[test]
+fn graph_with_roots_draws_missing_dependencies() {
+    let mut mod_links = read_mod_links();
+    mod_links.remove("Core");
+
+    let options = GraphOptions::builder().root("Boss").build().unwrap();
+    assert_eq!(
+        mod_links.to_dot(&options).unwrap(),
+        "digraph modlinks {\n\
+        \tnode [shape=box];\n\
+        \t\"Api\" [label=\"Api\"];\n\
+        \t\"Boss\" [label=\"Boss\"];\n\
+        \t\"Core\" [label=\"Core\", style=dotted];\n\
+        \t\"Ui\" [label=\"Ui\"];\n\
+        \t\"Api\" -> \"Core\";\n\
+        \t\"Boss\" -> \"Api\";\n\
+        \t\"Boss\" -> \"Ui\";\n\
+        \t\"Ui\" -> \"Core\";\n\
+        }\n"
+    );
+}
+
+#[test]
+fn graph_options_default_matches_builder() {
+    let built = GraphOptions::builder().build().unwrap();
+
+    assert_eq!(GraphOptions::default(), built);
+    assert!(built.integrations);
+
+    let mod_links = read_mod_links();
+    assert_eq!(
+        mod_links.to_dot(&GraphOptions::default()).unwrap(),
+        mod_links.to_dot(&built).unwrap()
+    );
+}
+
+#[test]
+fn resolve_error_has_dependency_chains() {
+    let mut mod_links = read_mod_links();
+    mod_links.remove("Core");
+
+    let err = mod_links
+        .resolve_deps(["Addon", "Fancy", "Gone"])
+        .unwrap_err();
+    let chains = err
+        .missing
+        .iter()
+        .map(|missing| missing.chain.clone())
+        .collect::<Vec<_>>();
+
+    assert_eq!(chains, [vec!["Addon", "Boss", "Api", "Core"], vec!["Gone"]]);
+    assert_eq!(
+        err.to_string(),
+        "Unknown mods:\n  Addon -> Boss -> Api -> Core\n  Gone"
+    );
+}
+
+fn sorted<'a>(mods: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    mods.into_iter()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+#[test]
+fn integrations_are_resolved_when_requested() {
+    let mut mod_links = read_mod_links();
+
+    let options = ResolveOptions::default();
+    let resolution = mod_links.resolve_with(["Extra"], &options).unwrap();
+    assert_eq!(sorted(resolution.mods), ["Core", "Extra"]);
+
+    let options = ResolveOptions::builder()
+        .integrations(true)
+        .build()
+        .unwrap();
+    let resolution = mod_links.resolve_with(["Extra"], &options).unwrap();
+    assert_eq!(
+        sorted(resolution.mods),
+        ["Api", "Boss", "Core", "Extra", "Ui"]
+    );
+    assert!(resolution.skipped.is_empty());
+
+    mod_links["Ui"]
+        .integrations
+        .insert("Standalone".to_string());
+
+    let resolution = mod_links.resolve_with(["Fancy"], &options).unwrap();
+    assert_eq!(sorted(resolution.mods), ["Core", "Fancy", "Ui"]);
+
+    let options = ResolveOptions::builder()
+        .integrations(true)
+        .transitive_integrations(true)
+        .build()
+        .unwrap();
+    let resolution = mod_links.resolve_with(["Fancy"], &options).unwrap();
+    assert_eq!(
+        sorted(resolution.mods),
+        ["Core", "Fancy", "Standalone", "Ui"]
+    );
+}
+
+#[test]
+fn skipped_integrations_are_reported() {
+    let mut mod_links = read_mod_links();
+    mod_links["Extra"].integrations.insert("Ghost".to_string());
+    mod_links["Addon"].dependencies.remove("Boss");
+    mod_links["Addon"].dependencies.insert("Gone".to_string());
+
+    let options = ResolveOptions::builder()
+        .integrations(true)
+        .exclude("Boss")
+        .build()
+        .unwrap();
+    let resolution = mod_links
+        .resolve_with(["Extra", "Fancy"], &options)
+        .unwrap();
+
+    assert_eq!(sorted(resolution.mods), ["Core", "Extra", "Fancy", "Ui"]);
+    assert_eq!(
+        resolution
+            .skipped
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        [
+            "Boss (integration of Extra): excluded",
+            "Ghost (integration of Extra): unavailable, missing Ghost",
+        ]
+    );
+    assert!(matches!(resolution.skipped[0].reason, SkipReason::Excluded));
+
+    mod_links["Fancy"].integrations.insert("Addon".to_string());
+    let resolution = mod_links.resolve_with(["Fancy"], &options).unwrap();
+    assert_eq!(
+        resolution.skipped[0].to_string(),
+        "Addon (integration of Fancy): unavailable, missing Addon -> Gone"
+    );
+}
+
+#[test]
+fn excluded_dependencies_are_left_out_of_install_plan() {
+    let mod_links = read_mod_links();
+    let options = ResolveOptions::builder().exclude("Api").build().unwrap();
+
+    let (plan, skipped) = mod_links.install_plan_with(["Addon"], &options).unwrap();
+    assert_eq!(plan.as_slice(), ["Core", "Ui", "Boss", "Addon"]);
+    assert!(skipped.is_empty());
+}
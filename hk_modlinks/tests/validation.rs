@@ -0,0 +1,260 @@
+use std::fs;
+use std::path::PathBuf;
+
+use url::Url;
+
+use hk_modlinks::{
+    FileDef, Links, LintConfig, LintConfigError, LintFinding, LintRule, Linter, ModInfo, ModLinks,
+    RuleConfig, Severity,
+};
+
+fn read_mod_links() -> ModLinks {
+    let path =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/relations/modlinks.xml");
+    ModLinks::from_xml(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[test]
+fn valid_mod_links_have_empty_report() {
+    let report = read_mod_links().validate();
+
+    assert!(report.is_empty(), "{report:?}");
+    assert!(!report.has_errors());
+}
+
+#[test]
+fn all_issues_are_collected() {
+    let mut mod_links = read_mod_links();
+    let core = mod_links.remove("Core").unwrap();
+    mod_links.insert("1Core".to_string(), core);
+    mod_links["Ui"].dependencies.insert("Boss".to_string());
+    mod_links["Fancy"].integrations.insert("Ghost".to_string());
+    mod_links["Standalone"].links = Links::new_platform_specific(
+        FileDef::new([0; 32], Url::parse("https://example.com/a.zip").unwrap()),
+        FileDef::new([0; 32], Url::parse("ftp://example.com/a.zip").unwrap()),
+        FileDef::new([0; 32], Url::parse("https://example.com/a.zip").unwrap()),
+    );
+
+    let report = mod_links.validate();
+    let issues = report
+        .iter()
+        .map(|i| (i.mod_name.as_str(), i.code.as_str(), i.field.as_str()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        issues,
+        [
+            ("1Core", "invalid-name", "name"),
+            ("Api", "unknown-dependency", "dependencies"),
+            ("Boss", "dependency-cycle", "dependencies"),
+            ("Extra", "unknown-dependency", "dependencies"),
+            ("Fancy", "unknown-integration", "integrations"),
+            ("Standalone", "invalid-file-url", "links.mac"),
+            ("Ui", "unknown-dependency", "dependencies"),
+        ]
+    );
+    assert!(report.iter().all(|i| i.severity == Severity::Error));
+    assert_eq!(report.count(Severity::Error), 7);
+    assert_eq!(
+        report.issues_for("Boss").next().unwrap().to_string(),
+        "error[dependency-cycle] Boss (dependencies): Dependency cycle Boss -> Ui -> Boss"
+    );
+}
+
+fn lint_issues(linter: &Linter, mod_links: &ModLinks) -> Vec<(String, Severity, String)> {
+    linter
+        .lint(mod_links)
+        .into_iter()
+        .map(|i| (i.mod_name, i.severity, i.code))
+        .collect()
+}
+
+fn lintable_mod_links() -> ModLinks {
+    let mut mod_links = read_mod_links();
+    mod_links
+        .inner_mut()
+        .retain(|name, _| name == "Api" || name == "Core");
+
+    let api = &mut mod_links["Api"];
+    api.description = " ".to_string();
+    api.display_name = Some("Api".to_string());
+    api.repository = Url::parse("http://example.com/Api").unwrap();
+
+    let core = &mut mod_links["Core"];
+    core.description = "x".repeat(501);
+    core.issues = Some(Url::parse("https://example.com/Core/issues").unwrap());
+    core.tags.insert(hk_modlinks::Tag::Library);
+
+    mod_links
+}
+
+#[test]
+fn builtin_lint_rules() {
+    let issues = lint_issues(&Linter::new(), &lintable_mod_links());
+
+    assert_eq!(
+        issues,
+        [
+            ("Api".into(), Severity::Warning, "empty-description".into()),
+            (
+                "Api".into(),
+                Severity::Info,
+                "redundant-display-name".into()
+            ),
+            ("Api".into(), Severity::Info, "missing-issues".into()),
+            ("Api".into(), Severity::Warning, "no-tags".into()),
+            (
+                "Api".into(),
+                Severity::Warning,
+                "non-https-repository".into()
+            ),
+            (
+                "Core".into(),
+                Severity::Warning,
+                "description-too-long".into()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn lint_config_from_toml() {
+    let config = toml::from_str::<LintConfig>(
+        r#"
+[rules.description-too-long]
+severity = "error"
+max-length = 1000
+
+[rules.no-tags]
+severity = "error"
+
+[rules.missing-issues]
+enabled = false
+
+[rules.redundant-display-name]
+enabled = false
+"#,
+    )
+    .unwrap();
+
+    let mut linter = Linter::new();
+    linter.configure(config).unwrap();
+    let issues = lint_issues(&linter, &lintable_mod_links());
+
+    assert_eq!(
+        issues,
+        [
+            ("Api".into(), Severity::Warning, "empty-description".into()),
+            ("Api".into(), Severity::Error, "no-tags".into()),
+            (
+                "Api".into(),
+                Severity::Warning,
+                "non-https-repository".into()
+            ),
+        ]
+    );
+
+    let config = toml::from_str::<LintConfig>("[rules.no-such-rule]\nenabled = false").unwrap();
+    assert_eq!(
+        linter.configure(config),
+        Err(LintConfigError::UnknownRule("no-such-rule".to_string()))
+    );
+    assert!(toml::from_str::<LintConfig>("[rules.no-tags]\ncolor = 1").is_err());
+}
+
+struct NoAuthors;
+
+impl LintRule for NoAuthors {
+    fn name(&self) -> &'static str {
+        "no-authors"
+    }
+
+    fn description(&self) -> &'static str {
+        "Mod has no authors"
+    }
+
+    fn check(&self, _: &str, info: &ModInfo, _: &RuleConfig) -> Vec<LintFinding> {
+        if info.authors.is_empty() {
+            vec![LintFinding::new("authors", "No authors")]
+        } else {
+            vec![]
+        }
+    }
+}
+
+#[test]
+fn custom_lint_rules() {
+    let mut linter = Linter::new_empty();
+    linter.add_rule(Box::new(NoAuthors));
+
+    assert_eq!(
+        lint_issues(&linter, &lintable_mod_links()),
+        [
+            ("Api".into(), Severity::Warning, "no-authors".into()),
+            ("Core".into(), Severity::Warning, "no-authors".into()),
+        ]
+    );
+}
+
+fn report_mod_links() -> ModLinks {
+    let mut mod_links = read_mod_links();
+    mod_links["Fancy"].integrations.insert("Ghost".to_string());
+    mod_links
+}
+
+#[test]
+fn junit_report_has_test_case_per_mod() {
+    let mod_links = report_mod_links();
+    let mut report = mod_links.validate();
+    report.extend(Linter::new().lint(&mod_links));
+    report.sort();
+
+    let junit = report
+        .to_junit("validate", mod_links.mod_names().map(String::as_str))
+        .unwrap();
+
+    assert!(junit.contains(r#"<testsuite name="validate" tests="8" failures="1" errors="0">"#));
+    assert_eq!(junit.matches("<testcase ").count(), 8);
+    assert!(junit.contains(
+        r#"<failure type="unknown-integration" message="integrations: Unknown mod Ghost">"#
+    ));
+    assert!(junit.contains("<system-out>info[missing-issues] Addon (issues): No issues URL\n"));
+}
+
+#[test]
+fn sarif_report_locates_issues() {
+    let report = report_mod_links().validate();
+    let sarif = report.to_sarif("hkml", Some("ModLinks.xml"));
+
+    assert_eq!(sarif["version"], "2.1.0");
+    let run = &sarif["runs"][0];
+    assert_eq!(
+        run["tool"]["driver"]["rules"],
+        serde_json::json!([{ "id": "unknown-integration" }])
+    );
+
+    let result = &run["results"][0];
+    assert_eq!(result["ruleId"], "unknown-integration");
+    assert_eq!(result["level"], "error");
+    assert_eq!(result["message"]["text"], "Fancy: Unknown mod Ghost");
+    assert_eq!(
+        result["locations"][0]["logicalLocations"][0]["fullyQualifiedName"],
+        "Fancy.integrations"
+    );
+    assert_eq!(
+        result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+        "ModLinks.xml"
+    );
+}
+
+#[test]
+fn json_report_counts_issues() {
+    let report = report_mod_links().validate();
+    let json = report.to_json();
+
+    assert_eq!(json["errors"], 1);
+    assert_eq!(json["warnings"], 0);
+    assert_eq!(json["issues"][0]["mod_name"], "Fancy");
+    assert_eq!(json["issues"][0]["severity"], "error");
+    assert_eq!(json["issues"][0]["field"], "integrations");
+}
@@ -1,7 +1,14 @@
+mod cache;
 mod changelog;
 mod convert;
+mod dependents;
 mod download;
 mod edit;
+mod graph;
+mod history;
+mod install;
+mod lint;
+mod outdated;
 mod resolve;
 mod validate;
 
@@ -13,10 +20,17 @@ use clap::{Args, Parser};
 
 use hk_modlinks::ModLinks;
 
+use cache::*;
 use changelog::*;
 use convert::*;
+use dependents::*;
 use download::*;
 use edit::*;
+use graph::*;
+use history::*;
+use install::*;
+use lint::*;
+use outdated::*;
 use resolve::*;
 use validate::*;
 
@@ -44,29 +58,57 @@ macro_rules! impl_run_inner {
 #[derive(Debug, Clone, Parser)]
 #[command(author, version, about)]
 pub enum Cli {
-    /// Resolve dependency of given mod(s) in the modlinks
+    /// Resolve dependency of given mod(s) in the modlinks, in install order
     Resolve(Resolve),
+    /// List mods that depend on or integrate with given mod in the modlinks
+    Dependents(Dependents),
     /// Download mod(s) with dependencies, as zip files by defaults
     Download(Download),
+    /// Install mod(s) with dependencies into a Hollow Knight installation
+    Install(Install),
+    /// Update mods installed by hkml, along with new dependencies
+    Update(Update),
+    /// Remove mods installed by hkml
+    Uninstall(Uninstall),
+    /// List mods installed by hkml with newer versions in the modlinks
+    Outdated(Outdated),
     /// Convert modlinks between different formats
     Convert(Convert),
     /// Validate mod relationships in the modlinks
     Validate(Validate),
+    /// Check content quality of mods in the modlinks
+    Lint(Lint),
+    /// Export the dependency graph as Graphviz DOT or Mermaid
+    Graph(Graph),
     /// Generate changelog between two modlinks
     Changelog(Changelog),
+    /// Generate changelog history across a series of modlinks snapshots
+    History(History),
     /// Edit the modlink
     #[command(subcommand)]
     Edit(Edit),
+    /// Manage the download cache
+    #[command(subcommand)]
+    Cache(Cache),
 }
 
 impl_run_inner! {
     Cli;
     Resolve,
+    Dependents,
     Download,
+    Install,
+    Update,
+    Uninstall,
+    Outdated,
     Convert,
     Validate,
+    Lint,
+    Graph,
     Changelog,
-    Edit
+    History,
+    Edit,
+    Cache
 }
 
 #[derive(Debug, Clone, Args)]
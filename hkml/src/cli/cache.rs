@@ -0,0 +1,146 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+use clap::{Args, Subcommand};
+
+use indicatif::HumanBytes;
+
+use super::Run;
+use crate::{impl_run_inner, parse_size, DownloadCache, Result, VerificationCache};
+
+const DEFAULT_CACHE_MAX_SIZE: &str = "1GiB";
+
+/// Options for the download cache shared by commands fetching mod files.
+#[derive(Args, Debug, Clone)]
+pub struct CacheArgs {
+    /// Keep downloaded files in the directory and reuse them by hash
+    #[arg(long, value_name = "DIR", env = "HKML_CACHE_DIR")]
+    cache_dir: Option<PathBuf>,
+    /// Remove least recently used files when the cache grows larger than this
+    #[arg(
+        long,
+        value_name = "SIZE",
+        default_value = DEFAULT_CACHE_MAX_SIZE,
+        value_parser = parse_size,
+        env = "HKML_CACHE_MAX_SIZE"
+    )]
+    cache_max_size: u64,
+}
+
+impl CacheArgs {
+    #[inline]
+    pub fn cache_dir(&self) -> Option<&Path> {
+        self.cache_dir.as_deref()
+    }
+
+    pub fn open(&self) -> Result<Option<DownloadCache>> {
+        self.cache_dir
+            .as_deref()
+            .map(|dir| DownloadCache::open(dir, self.cache_max_size))
+            .transpose()
+    }
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CacheDirArgs {
+    /// Cache directory
+    #[arg(long, value_name = "DIR", env = "HKML_CACHE_DIR")]
+    cache_dir: PathBuf,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum Cache {
+    /// List cached files, most recently used first
+    Ls(CacheLs),
+    /// Remove least recently used files until the cache fits in a size
+    Gc(CacheGc),
+    /// Remove all cached files and verifications
+    Clear(CacheClear),
+}
+
+impl_run_inner! {
+    Cache;
+    Ls,
+    Gc,
+    Clear
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CacheLs {
+    #[command(flatten)]
+    dir_args: CacheDirArgs,
+}
+
+impl Run for CacheLs {
+    fn run(self) -> Result {
+        let cache = DownloadCache::open(&self.dir_args.cache_dir, u64::MAX)?;
+        let entries = cache.entries()?;
+
+        for info in &entries {
+            println!(
+                "{}  {:>10}  {}  {}  {}",
+                &info.sha256[..12],
+                HumanBytes(info.size).to_string(),
+                humantime::format_rfc3339_seconds(UNIX_EPOCH + Duration::from_secs(info.last_used)),
+                info.file_name.as_deref().unwrap_or("-"),
+                info.url
+            );
+        }
+
+        println!(
+            "{} file(s), {} in total",
+            entries.len(),
+            HumanBytes(entries.iter().map(|info| info.size).sum())
+        );
+
+        Ok(())
+    }
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CacheGc {
+    #[command(flatten)]
+    dir_args: CacheDirArgs,
+    /// Size to shrink the cache to
+    #[arg(
+        long,
+        value_name = "SIZE",
+        default_value = DEFAULT_CACHE_MAX_SIZE,
+        value_parser = parse_size,
+        env = "HKML_CACHE_MAX_SIZE"
+    )]
+    max_size: u64,
+}
+
+impl Run for CacheGc {
+    fn run(self) -> Result {
+        let cache = DownloadCache::open(&self.dir_args.cache_dir, self.max_size)?;
+        let removed = cache.gc(self.max_size)?;
+
+        println!(
+            "Removed {} file(s), freeing {}",
+            removed.len(),
+            HumanBytes(removed.iter().map(|info| info.size).sum())
+        );
+
+        Ok(())
+    }
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CacheClear {
+    #[command(flatten)]
+    dir_args: CacheDirArgs,
+}
+
+impl Run for CacheClear {
+    fn run(self) -> Result {
+        let dir = &self.dir_args.cache_dir;
+        let removed = DownloadCache::open(dir, u64::MAX)?.clear()?;
+        VerificationCache::clear(dir)?;
+
+        println!("Removed {removed} file(s)");
+
+        Ok(())
+    }
+}
@@ -1,12 +1,33 @@
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::Write;
 use std::path::PathBuf;
+use std::time::SystemTime;
 
-use clap::Args;
+use clap::{Args, ValueEnum};
+
+use hk_modlinks::{
+    atom_entry_timestamps, AtomFeedOptions, ChangelogRenderer, HTML_TEMPLATE_NAME,
+    MARKDOWN_TEMPLATE_NAME, PLAIN_TEMPLATE_NAME,
+};
+
+use url::Url;
 
 use super::{InArgs, Run};
 use crate::Result;
 
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum ChangelogFormat {
+    #[default]
+    Markdown,
+    /// The changelog context, see `hk_modlinks::CHANGELOG_JSON_SCHEMA`
+    Json,
+    /// HTML fragment
+    Html,
+    Plain,
+    /// Atom feed with an entry for each new or updated mod
+    Atom,
+}
+
 #[derive(Args, Debug, Clone)]
 pub struct Changelog {
     #[arg(value_name = "OLD FILE")]
@@ -16,6 +37,48 @@ pub struct Changelog {
 
     #[arg(short, long, value_name = "FILE")]
     out: Option<PathBuf>,
+    /// Output format
+    #[arg(short, long, value_name = "FORMAT", default_value_t, value_enum)]
+    format: ChangelogFormat,
+    /// Render with a custom Handlebars template instead of a built-in format
+    #[arg(short, long, value_name = "FILE", conflicts_with = "format")]
+    template: Option<PathBuf>,
+    /// Register a Handlebars partial named after the file stem, can be repeated
+    #[arg(short, long, value_name = "FILE", requires = "template")]
+    partial: Vec<PathBuf>,
+
+    /// Atom feed id, should stay the same across regenerations
+    #[arg(
+        long,
+        value_name = "IRI",
+        default_value = "urn:hk-modlinks:changelog",
+        help_heading = "Atom"
+    )]
+    feed_id: String,
+    #[arg(
+        long,
+        value_name = "TITLE",
+        default_value = "Hollow Knight mod updates",
+        help_heading = "Atom"
+    )]
+    feed_title: String,
+    /// Feed author, defaults to hk_modlinks when a mod has no authors
+    #[arg(long, value_name = "NAME", help_heading = "Atom")]
+    feed_author: Option<String>,
+    /// URL where the feed is published
+    #[arg(long, value_name = "URL", help_heading = "Atom")]
+    feed_self_link: Option<Url>,
+    /// RFC 3339 timestamp of the feed and its new entries, defaults to now
+    #[arg(
+        long,
+        value_name = "TIMESTAMP",
+        value_parser = humantime::parse_rfc3339,
+        help_heading = "Atom"
+    )]
+    feed_updated: Option<SystemTime>,
+    /// Previously generated feed, whose entries keep their timestamps
+    #[arg(long, value_name = "FILE", help_heading = "Atom")]
+    feed_previous: Option<PathBuf>,
 }
 
 impl Run for Changelog {
@@ -23,15 +86,72 @@ impl Run for Changelog {
         let old_mod_links = InArgs::read_from_file(self.from)?;
         let new_mod_links = InArgs::read_from_file(self.to)?;
 
-        let changelog = new_mod_links
-            .changelog_since(&old_mod_links)
-            .to_markdown()?;
+        if let ChangelogFormat::Atom = self.format {
+            let updated = self.feed_updated.unwrap_or_else(SystemTime::now);
+
+            let mut options = AtomFeedOptions::builder();
+            options
+                .id(self.feed_id)
+                .title(self.feed_title)
+                .updated(humantime::format_rfc3339_seconds(updated).to_string());
+            if let Some(path) = self.feed_previous {
+                options.entry_updated(
+                    atom_entry_timestamps(&fs::read_to_string(&path)?)
+                        .map_err(|e| format!("Invalid feed {}: {e}", path.display()))?,
+                );
+            }
+            if let Some(author) = self.feed_author {
+                options.author(author);
+            }
+            if let Some(self_link) = self.feed_self_link {
+                options.self_link(self_link);
+            }
+
+            let feed = new_mod_links.atom_since(&old_mod_links, &options.build()?)?;
+            return write_output(self.out, feed);
+        }
 
-        match self.out {
-            Some(path) => File::create(path)?.write_all(changelog.as_bytes())?,
-            None => println!("{changelog}"),
+        let changelog = new_mod_links.changelog_since(&old_mod_links);
+
+        if let ChangelogFormat::Json = self.format {
+            return write_output(self.out, changelog.to_json()?);
+        }
+
+        let mut renderer = ChangelogRenderer::new();
+
+        for path in self.partial {
+            let name = path
+                .file_stem()
+                .ok_or_else(|| format!("Invalid partial path: {}", path.display()))?
+                .to_string_lossy()
+                .into_owned();
+            renderer.register_partial(&name, fs::read_to_string(&path)?)?;
+        }
+
+        let template_name = match self.template {
+            Some(path) => {
+                let name = path.display().to_string();
+                renderer.register_template_file(&name, &path)?;
+                name
+            }
+            None => match self.format {
+                ChangelogFormat::Markdown => MARKDOWN_TEMPLATE_NAME,
+                ChangelogFormat::Html => HTML_TEMPLATE_NAME,
+                ChangelogFormat::Plain => PLAIN_TEMPLATE_NAME,
+                ChangelogFormat::Json | ChangelogFormat::Atom => unreachable!(),
+            }
+            .to_string(),
         };
 
-        Ok(())
+        write_output(self.out, renderer.render(&template_name, &changelog)?)
     }
 }
+
+pub(super) fn write_output(out: Option<PathBuf>, changelog: String) -> Result {
+    match out {
+        Some(path) => File::create(path)?.write_all(changelog.as_bytes())?,
+        None => println!("{changelog}"),
+    };
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use clap::Args;
+
+use itertools::Itertools;
+
+use super::{InArgs, Run};
+use crate::Result;
+
+#[derive(Args, Debug, Clone)]
+pub struct Dependents {
+    #[command(flatten)]
+    in_args: InArgs,
+
+    #[arg(value_name = "MOD")]
+    name: String,
+    /// Include mods depending on it through other mods
+    #[arg(short, long)]
+    transitive: bool,
+}
+
+impl Run for Dependents {
+    fn run(self) -> Result {
+        let mod_links = self.in_args.read()?;
+
+        if !mod_links.contains(&self.name) {
+            Err(format!("Unknown mod: {}", self.name))?;
+        }
+
+        let dependents = if self.transitive {
+            mod_links.dependents_transitive(&self.name)
+        } else {
+            mod_links.dependents(&self.name)
+        };
+
+        if !dependents.required_by.is_empty() {
+            println!(
+                "Dependencies:\n{}",
+                dependents.required_by.iter().join("\n")
+            );
+        }
+
+        if !dependents.integrated_by.is_empty() {
+            println!(
+                "Integrations:\n{}",
+                dependents.integrated_by.iter().join("\n")
+            );
+        }
+
+        Ok(())
+    }
+}
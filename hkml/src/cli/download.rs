@@ -1,28 +1,28 @@
-use std::fs::{self, File};
-use std::io::{self, Cursor};
+use std::fs::File;
+use std::io;
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
 
-use actix_web::http::header::{ContentDisposition, CONTENT_DISPOSITION, CONTENT_LENGTH};
-
 use clap::Args;
 
+use indicatif::MultiProgress;
+
 use itertools::Itertools;
 
 use lazy_static::lazy_static;
 
 use ureq::Agent;
 
-use url::Url;
-
-use sha2::{Digest, Sha256};
-
 use zip::{write::SimpleFileOptions as ZipFileOptions, ZipArchive, ZipWriter};
 
 use hk_modlinks::{FileDef, Links, Platform};
 
-use super::resolve::read_mods_from_vec_or_file;
-use super::{InArgs, Run};
-use crate::{copy_pb_buf_read, copy_pb_slice, Result};
+use super::resolve::{read_mods_from_vec_or_file, ResolveArgs};
+use super::{CacheArgs, InArgs, Run};
+use crate::{
+    copy_pb_buf_read, count_progress_bar, hash_into, map_concurrent, parent_dir, DownloadCache,
+    Result, Retry, Staging, TempFile, VerifiedFile,
+};
 
 lazy_static! {
     // It is assumed that no mods exceeds the 4 GiB limit
@@ -30,6 +30,7 @@ lazy_static! {
         ZipFileOptions::default().compression_level(Some(264));
 }
 
+#[allow(clippy::duplicated_attributes)]
 #[derive(Args, Debug, Clone)]
 #[group(id = "operation", multiple = false)]
 #[group(id = "mod", required = true, multiple = false)]
@@ -46,29 +47,100 @@ pub struct Download {
     #[arg(short, long, value_name = "FILE|DIR")]
     out: PathBuf,
     /// Do not resolve dependencies
-    #[arg(long)]
+    #[arg(long, conflicts_with_all = ["with_integrations", "exclude"])]
     no_deps: bool,
-    /// Platform to download for, defaults to local platform
-    #[arg(long)]
-    platform: Option<Platform>,
+    #[command(flatten)]
+    resolve_args: ResolveArgs,
     /// Unpack mod zips into subdirectories, output path should be a directory.
     #[arg(long, group = "operation")]
     unpack: bool,
     /// Repack unpacked mod zips into a single zip file, output path should be a file.
     #[arg(long, group = "operation")]
     repack: bool,
+    #[command(flatten)]
+    fetch_args: FetchArgs,
+}
+
+/// Options shared by commands downloading mod files.
+#[derive(Args, Debug, Clone)]
+pub struct FetchArgs {
+    /// Platform to download for, defaults to local platform
+    #[arg(long)]
+    platform: Option<Platform>,
+    /// Number of files to download at a time
+    #[arg(short, long, value_name = "N", default_value = "4")]
+    jobs: NonZeroUsize,
+    /// Times to retry a download after a transient failure, waiting twice as
+    /// long before each retry
+    #[arg(long, value_name = "N", default_value = "3")]
+    retries: u32,
+    #[command(flatten)]
+    cache_args: CacheArgs,
+}
+
+impl FetchArgs {
+    #[inline]
+    pub fn platform(&self) -> Platform {
+        self.platform.unwrap_or(Platform::LOCAL)
+    }
+
+    #[inline]
+    pub fn open_cache(&self) -> Result<Option<DownloadCache>> {
+        self.cache_args.open()
+    }
+
+    /// [`fetch_files`] with the configured number of jobs and retries.
+    #[inline]
+    pub fn fetch_files(
+        &self,
+        files: &[(&str, &FileDef)],
+        cache: Option<&DownloadCache>,
+        staging: &Staging,
+    ) -> Vec<Result<VerifiedFile, String>> {
+        fetch_files(
+            &crate::AGENT,
+            files,
+            cache,
+            staging,
+            self.jobs,
+            Retry::new(self.retries),
+        )
+    }
+}
+
+/// Clean up `staging` once fetched files were processed, listing the mods
+/// that failed and returning an error naming them, if any.
+pub fn finish_fetch(staging: &Staging, failed: &[(&str, String)], action: &str) -> Result {
+    // Kept if anything failed, to be resumed next time
+    staging.remove_if_empty();
+
+    for (name, e) in failed {
+        println!("  {name}: {e}");
+    }
+
+    if !failed.is_empty() {
+        Err(format!(
+            "Failed to {action} {}",
+            failed.iter().map(|(name, _)| name).join(", ")
+        ))?;
+    }
+
+    Ok(())
 }
 
 impl Run for Download {
     fn run(self) -> Result {
         let mod_links = self.in_args.read()?;
-        let platform = self.platform.map(Into::into).unwrap_or(Platform::LOCAL);
+        let platform = self.fetch_args.platform();
+        let cache = self.fetch_args.open_cache()?;
+        let cache = cache.as_ref();
 
         let out = self.out;
+        let out_parent = parent_dir(&out)?;
         if self.unpack {
             fs_extra::dir::create_all(&out, true)?;
         } else {
-            fs_extra::dir::create_all(out.parent().unwrap(), false)?;
+            fs_extra::dir::create_all(out_parent, false)?;
         }
 
         let mods = read_mods_from_vec_or_file(self.mods, self.mods_file)?;
@@ -76,34 +148,54 @@ impl Run for Download {
         let mods = if self.no_deps {
             mods
         } else {
-            mod_links
-                .resolve_deps(mods.iter().map(String::as_str))
-                .map_err(|u| format!("Unknown mods: {}", u.join(", ")))?
+            self.resolve_args
+                .install_plan(&mod_links, &mods)?
                 .into_iter()
                 .map(ToString::to_string)
                 .collect_vec()
         };
 
+        let files = mods
+            .iter()
+            .map(|name| {
+                let file = match &mod_links.get(name).unwrap().links {
+                    Links::Universal(file) => file,
+                    Links::PlatformSpecific {
+                        windows,
+                        mac,
+                        linux,
+                    } => match platform {
+                        Platform::Windows => windows,
+                        Platform::Mac => mac,
+                        Platform::Linux => linux,
+                    },
+                };
+
+                (name.as_str(), file)
+            })
+            .collect_vec();
+
+        // Outside of the output directory, which is emptied when unpacking
+        let staging = Staging::for_download(cache, out_parent);
+        let fetched = self.fetch_args.fetch_files(&files, cache, &staging);
+
         let mut zip = if self.repack {
             Some(ZipWriter::new(File::create(&out)?))
         } else {
             None
         };
 
-        let mut process_fn: Box<dyn FnMut(_, _) -> Result> = if self.unpack {
-            Box::new(|name: String, file: &FileDef| {
-                download_to_dir(crate::AGENT.clone(), file, out.join(&name), name)?;
-                Ok(())
+        let mut process_fn: Box<dyn FnMut(_, _, _) -> Result> = if self.unpack {
+            Box::new(|name: &str, _: &FileDef, verified: VerifiedFile| {
+                unpack_to_dir(verified, out.join(name), name)
             })
         } else if self.repack {
-            Box::new(|name: String, file: &FileDef| {
+            Box::new(|name: &str, _: &FileDef, verified: VerifiedFile| {
                 let zip = zip.as_mut().unwrap();
-                zip.add_directory(&name, *BEST_COMPRESSION)?;
-
-                let (buf, file_name) = download_and_verify(crate::AGENT.clone(), file)?;
+                zip.add_directory(name, *BEST_COMPRESSION)?;
 
-                if infer::archive::is_zip(&buf) {
-                    let mut mod_zip = ZipArchive::new(Cursor::new(buf))?;
+                if verified.is_zip()? {
+                    let mut mod_zip = ZipArchive::new(verified.open()?)?;
                     for i in 0..mod_zip.len() {
                         let mut file = mod_zip.by_index(i)?;
 
@@ -122,39 +214,33 @@ impl Run for Download {
                     zip.start_file(
                         format!(
                             "{name}/{}",
-                            file_name.unwrap_or_else(|| format!("{name}.dll"))
+                            verified
+                                .file_name
+                                .clone()
+                                .unwrap_or_else(|| format!("{name}.dll"))
                         ),
                         *BEST_COMPRESSION,
                     )?;
-                    copy_pb_slice(&buf, zip, "Compressing")?;
+                    let size = verified.size()? as usize;
+                    copy_pb_buf_read(&mut verified.open()?, zip, Some(size), "Compressing")?;
                 }
 
                 Ok(())
             })
         } else {
-            Box::new(|name: String, file: &FileDef| {
-                download_to_zip(crate::AGENT.clone(), file, &out, name)?;
+            Box::new(|name: &str, file: &FileDef, verified: VerifiedFile| {
+                zip_to(verified, file, &out, name)?;
                 Ok(())
             })
         };
 
-        for name in mods {
-            println!("Downloading {name}");
-
-            let file = match &mod_links.get(&name).unwrap().links {
-                Links::Universal(file) => file,
-                Links::PlatformSpecific {
-                    windows,
-                    mac,
-                    linux,
-                } => match platform {
-                    Platform::Windows => windows,
-                    Platform::Mac => mac,
-                    Platform::Linux => linux,
-                },
-            };
-
-            process_fn(name, file)?;
+        // Written in install order, no matter which download finished first
+        let mut failed = vec![];
+        for (&(name, file), result) in files.iter().zip(fetched) {
+            match result {
+                Ok(verified) => process_fn(name, file, verified)?,
+                Err(e) => failed.push((name, e)),
+            }
         }
 
         drop(process_fn);
@@ -163,114 +249,172 @@ impl Run for Download {
             zip.finish()?;
         }
 
-        Ok(())
+        println!(
+            "Downloaded {} mod(s), {} failed",
+            files.len() - failed.len(),
+            failed.len()
+        );
+
+        finish_fetch(&staging, &failed, "download")
     }
 }
 
-pub fn download_and_verify(agent: Agent, file: &FileDef) -> Result<(Vec<u8>, Option<String>)> {
-    let resp = agent.get(file.url.as_str()).call()?;
-
-    let disposition = resp
-        .header(CONTENT_DISPOSITION.as_str())
-        .and_then(|header| {
-            ContentDisposition::from_raw(
-                &actix_web::http::header::HeaderValue::from_bytes(header.as_bytes())
-                    .expect("failed to perform identity transformation on header value"),
+/// Download files of mods with at most `jobs` at a time, retrying transient
+/// failures. Results are in the same order as the files.
+pub fn fetch_files(
+    agent: &Agent,
+    files: &[(&str, &FileDef)],
+    cache: Option<&DownloadCache>,
+    staging: &Staging,
+    jobs: NonZeroUsize,
+    retry: Retry,
+) -> Vec<Result<VerifiedFile, String>> {
+    let multi = MultiProgress::new();
+    let overall = multi.add(count_progress_bar(files.len(), "Downloading"));
+
+    let results = map_concurrent(files, jobs, |&(name, file)| {
+        let result = retry
+            .run(
+                || fetch_file(agent, file, cache, staging, &multi, name.to_string()),
+                |e, delay| {
+                    multi
+                        .println(format!(
+                            "Retrying {name} in {}: {e}",
+                            humantime::format_duration(delay)
+                        ))
+                        .ok();
+                },
             )
-            .unwrap()
-            .get_filename()
-            .map(ToOwned::to_owned)
-        })
-        .or_else(|| {
-            Url::parse(resp.get_url())
-                .unwrap()
-                .path_segments()
-                .and_then(|segments| segments.last().map(ToOwned::to_owned))
-        });
-
-    let buf = {
-        let size = resp
-            .header(CONTENT_LENGTH.as_str())
-            .and_then(|i| i.parse::<usize>().ok());
-        let mut buf = Vec::with_capacity(size.unwrap_or(crate::DEFAULT_BUF_SIZE));
-
-        copy_pb_buf_read(&mut resp.into_reader(), &mut buf, size, "Downloading")?;
-
-        buf
-    };
-
-    let hash: [u8; 32] = {
-        let mut hasher = <Sha256 as Digest>::new();
-        copy_pb_slice(&buf, &mut hasher, "Hashing")?;
-        Digest::finalize(hasher).into()
-    };
-    if hash != file.sha256 {
-        Err(io::Error::other(format!(
-            "Hash mismatch!\n  Expected: {}\n  Actual: {}",
-            hex::encode_upper(file.sha256),
-            hex::encode_upper(hash)
-        )))?;
-    };
-
-    Ok((buf, disposition))
+            .map_err(|e| e.to_string());
+
+        if let Err(e) = &result {
+            multi.println(format!("Failed {name}: {e}")).ok();
+        }
+        overall.inc(1);
+
+        result
+    });
+
+    overall.finish_and_clear();
+
+    results
+}
+
+/// Download a file through `staging` while checking its hash.
+///
+/// The file is taken from the cache instead if there, and added to it after
+/// downloading otherwise.
+pub fn download_and_verify(
+    agent: Agent,
+    file: &FileDef,
+    cache: Option<&DownloadCache>,
+    staging: &Staging,
+) -> Result<VerifiedFile> {
+    fetch_file(
+        &agent,
+        file,
+        cache,
+        staging,
+        &MultiProgress::new(),
+        "Downloading".to_string(),
+    )
+}
+
+fn fetch_file(
+    agent: &Agent,
+    file: &FileDef,
+    cache: Option<&DownloadCache>,
+    staging: &Staging,
+    multi: &MultiProgress,
+    label: String,
+) -> Result<VerifiedFile> {
+    if let Some(verified) = cache.map(|cache| cache.get(file)).transpose()?.flatten() {
+        multi
+            .println(format!(
+                "Using cached {}",
+                verified.file_name.as_deref().unwrap_or(&file.sha256())
+            ))
+            .ok();
+        return Ok(verified);
+    }
+
+    let (verified, validators) = staging.download(agent, file, multi, label)?;
+    Ok(match cache {
+        Some(cache) => cache.commit(verified, file, validators)?,
+        None => verified,
+    })
 }
 
+/// Download a file as a zip at `dest`, returning the hash of the zip.
 pub fn download_and_zip(
     agent: Agent,
     file: &FileDef,
+    cache: Option<&DownloadCache>,
+    staging: &Staging,
+    dest: &Path,
     fallback_name: impl AsRef<str>,
-) -> Result<Vec<u8>> {
-    let (buf, name) = download_and_verify(agent, file)?;
-    let file_name = name.unwrap_or_else(|| format!("{}.dll", fallback_name.as_ref()));
+) -> Result<[u8; 32]> {
+    let verified = download_and_verify(agent, file, cache, staging)?;
+    zip_to(verified, file, dest, fallback_name)
+}
 
-    if infer::archive::is_zip(&buf) {
-        return Ok(buf);
+/// Move a verified file to `dest`, wrapped in a zip commented with its hash
+/// unless it is a zip already. Returns the hash of the zip.
+fn zip_to(
+    verified: VerifiedFile,
+    file: &FileDef,
+    dest: &Path,
+    fallback_name: impl AsRef<str>,
+) -> Result<[u8; 32]> {
+    if verified.is_zip()? {
+        verified.persist(dest)?;
+        return Ok(file.sha256);
     }
 
-    let mut zip_writer = ZipWriter::new(Cursor::new(vec![]));
+    let file_name = verified
+        .file_name
+        .clone()
+        .unwrap_or_else(|| format!("{}.dll", fallback_name.as_ref()));
+
+    let mut zip_writer = ZipWriter::new(TempFile::new_in(parent_dir(dest)?)?);
     zip_writer.set_comment(hex::encode_upper(file.sha256));
 
     zip_writer.start_file(
         file_name,
         ZipFileOptions::default().compression_level(Some(9)),
     )?;
-    copy_pb_slice(&buf, &mut zip_writer, "Compressing")?;
-    drop(buf);
+    let size = verified.size()? as usize;
+    copy_pb_buf_read(
+        &mut verified.open()?,
+        &mut zip_writer,
+        Some(size),
+        "Compressing",
+    )?;
 
-    Ok(zip_writer.finish()?.into_inner())
+    let zip = zip_writer.finish()?.into_verified(None)?;
+    let (_, hash, _) = hash_into(zip.open()?, io::sink())?;
+    zip.persist(dest)?;
+
+    Ok(hash)
 }
 
-pub fn download_to_dir(
-    agent: Agent,
-    file: &FileDef,
+fn unpack_to_dir(
+    verified: VerifiedFile,
     dest: impl AsRef<Path>,
     fallback_name: impl AsRef<str>,
 ) -> Result {
-    let (buf, name) = download_and_verify(agent, file)?;
     let dest = dest.as_ref();
-    let file_name = name.unwrap_or_else(|| format!("{}.dll", fallback_name.as_ref()));
 
-    if !infer::archive::is_zip(&buf) {
-        fs::write(dest.with_file_name(file_name), buf)?;
+    if !verified.is_zip()? {
+        let file_name = verified
+            .file_name
+            .clone()
+            .unwrap_or_else(|| format!("{}.dll", fallback_name.as_ref()));
+        verified.persist(&dest.with_file_name(file_name))?;
         return Ok(());
     }
 
-    ZipArchive::new(Cursor::new(buf))?.extract(dest)?;
-
-    Ok(())
-}
-
-pub fn download_to_zip(
-    agent: Agent,
-    file: &FileDef,
-    dest: impl AsRef<Path>,
-    fallback_name: impl AsRef<str>,
-) -> Result {
-    let buf = download_and_zip(agent, file, fallback_name)?;
-
-    let mut file = File::create(dest)?;
-    copy_pb_slice(&buf, &mut file, "Writing")?;
-    file.sync_all()?;
+    ZipArchive::new(verified.open()?)?.extract(dest)?;
 
     Ok(())
 }
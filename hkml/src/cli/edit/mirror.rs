@@ -3,17 +3,13 @@ use std::{fs, io};
 
 use clap::Args;
 
-use ureq::Agent;
-
-use sha2::{Digest, Sha256};
-
 use url::Url;
 
 use hk_modlinks::{get_safe_mod_name, FileDef, Links, ModInfo};
 
 use super::{InArgs, Run};
-use crate::cli::download_and_zip;
-use crate::Result;
+use crate::cli::{download_and_zip, CacheArgs};
+use crate::{parent_dir, DownloadCache, Result, Staging};
 
 #[derive(Args, Debug, Clone)]
 pub struct Mirror {
@@ -26,11 +22,14 @@ pub struct Mirror {
     prev: Option<PathBuf>,
     #[command(flatten)]
     in_args: InArgs,
+    #[command(flatten)]
+    cache_args: CacheArgs,
 }
 
 impl Run for Mirror {
     fn run(self) -> Result {
         let mut mod_links = self.in_args.read()?;
+        let cache = self.cache_args.open()?;
 
         let base_url = self.base_url;
         assert!(
@@ -51,6 +50,8 @@ impl Run for Mirror {
         let mods_dir = base_dir.join("mods");
         fs_extra::dir::create_all(&mods_dir, true)?;
         let mods_dir = fs::canonicalize(mods_dir)?;
+        // Outside of the output directory, which gets published as is
+        let staging = Staging::for_download(cache.as_ref(), parent_dir(&base_dir)?);
 
         let prev_base_dir = self.prev.map(fs::canonicalize).transpose()?;
         let prev_mods_dir = prev_base_dir
@@ -76,159 +77,163 @@ impl Run for Mirror {
             .transpose()?;
         let prev_mods_url = prev_base_url.map(|x| x.join("mods/")).transpose()?;
 
-        mod_links
-            .iter_mut()
-            .try_for_each(|(name, info)| -> Result {
-                let base_name = format!("{}-v{}", get_safe_mod_name(name), info.version);
-                println!("Downloading {name} as {base_name}");
-
-                let prev_orig_info = prev_orig_mod_links.as_ref().and_then(|x| x.get(name));
+        let result = mod_links.iter_mut().try_for_each(|(name, info)| -> Result {
+            let base_name = format!("{}-v{}", get_safe_mod_name(name), info.version);
+            println!("Downloading {name} as {base_name}");
+
+            let prev_orig_info = prev_orig_mod_links.as_ref().and_then(|x| x.get(name));
+
+            match &mut info.links {
+                Links::Universal(file) => {
+                    if let Some(ModInfo {
+                        links: Links::Universal(prev_orig_file),
+                        ..
+                    }) = prev_orig_info
+                    {
+                        if prev_orig_file.sha256 == file.sha256 {
+                            let prev_file = match prev_mod_links.as_ref().unwrap().get(name) {
+                                Some(ModInfo {
+                                    links: Links::Universal(prev_file),
+                                    ..
+                                }) => prev_file,
+                                _ => panic!("Invalid previous mirror"),
+                            };
 
-                match &mut info.links {
-                    Links::Universal(file) => {
-                        if let Some(ModInfo {
-                            links: Links::Universal(prev_orig_file),
-                            ..
-                        }) = prev_orig_info
-                        {
-                            if prev_orig_file.sha256 == file.sha256 {
-                                let prev_file = match prev_mod_links.as_ref().unwrap().get(name) {
-                                    Some(ModInfo {
-                                        links: Links::Universal(prev_file),
-                                        ..
-                                    }) => prev_file,
-                                    _ => panic!("Invalid previous mirror"),
-                                };
-
-                                if migrate(
-                                    prev_file,
-                                    file,
-                                    prev_mods_dir.as_ref().unwrap(),
-                                    &mods_dir,
-                                    prev_mods_url.as_ref().unwrap(),
-                                    &mods_url,
-                                )? {
-                                    return Ok(());
-                                }
+                            if migrate(
+                                prev_file,
+                                file,
+                                prev_mods_dir.as_ref().unwrap(),
+                                &mods_dir,
+                                prev_mods_url.as_ref().unwrap(),
+                                &mods_url,
+                            )? {
+                                return Ok(());
                             }
                         }
-
-                        download_and_update(
-                            crate::AGENT.clone(),
-                            file,
-                            &mods_dir,
-                            format!("{base_name}.zip"),
-                            &mods_url,
-                            &base_name,
-                        )?;
                     }
-                    Links::PlatformSpecific {
-                        windows,
-                        mac,
-                        linux,
-                    } => {
-                        let (mut windows_ok, mut mac_ok, mut linux_ok) = (false, false, false);
-
-                        if let Some(ModInfo {
+
+                    download_and_update(
+                        file,
+                        cache.as_ref(),
+                        &staging,
+                        &mods_dir,
+                        format!("{base_name}.zip"),
+                        &mods_url,
+                        &base_name,
+                    )?;
+                }
+                Links::PlatformSpecific {
+                    windows,
+                    mac,
+                    linux,
+                } => {
+                    let (mut windows_ok, mut mac_ok, mut linux_ok) = (false, false, false);
+
+                    if let Some(ModInfo {
+                        links:
+                            Links::PlatformSpecific {
+                                windows: prev_orig_windows,
+                                mac: prev_orig_mac,
+                                linux: prev_orig_linux,
+                            },
+                        ..
+                    }) = prev_orig_info
+                    {
+                        let prev_mods_dir = prev_mods_dir.as_ref().unwrap();
+                        let prev_mods_url = prev_mods_url.as_ref().unwrap();
+                        let Some(ModInfo {
                             links:
                                 Links::PlatformSpecific {
-                                    windows: prev_orig_windows,
-                                    mac: prev_orig_mac,
-                                    linux: prev_orig_linux,
+                                    windows: prev_windows,
+                                    mac: prev_mac,
+                                    linux: prev_linux,
                                 },
                             ..
-                        }) = prev_orig_info
-                        {
-                            let prev_mods_dir = prev_mods_dir.as_ref().unwrap();
-                            let prev_mods_url = prev_mods_url.as_ref().unwrap();
-                            let Some(ModInfo {
-                                links:
-                                    Links::PlatformSpecific {
-                                        windows: prev_windows,
-                                        mac: prev_mac,
-                                        linux: prev_linux,
-                                    },
-                                ..
-                            }) = prev_mod_links.as_ref().unwrap().get(name)
-                            else {
-                                panic!("Invalid previous mirror")
-                            };
-
-                            if prev_orig_windows.sha256 == windows.sha256
-                                && migrate(
-                                    prev_windows,
-                                    windows,
-                                    prev_mods_dir,
-                                    &mods_dir,
-                                    prev_mods_url,
-                                    &mods_url,
-                                )?
-                            {
-                                windows_ok = true;
-                            }
-
-                            if prev_orig_mac.sha256 == mac.sha256
-                                && migrate(
-                                    prev_mac,
-                                    mac,
-                                    prev_mods_dir,
-                                    &mods_dir,
-                                    prev_mods_url,
-                                    &mods_url,
-                                )?
-                            {
-                                mac_ok = true;
-                            }
-
-                            if prev_orig_linux.sha256 == linux.sha256
-                                && migrate(
-                                    prev_linux,
-                                    linux,
-                                    prev_mods_dir,
-                                    &mods_dir,
-                                    prev_mods_url,
-                                    &mods_url,
-                                )?
-                            {
-                                linux_ok = true;
-                            }
-                        }
-
-                        if !windows_ok {
-                            download_and_update(
-                                crate::AGENT.clone(),
+                        }) = prev_mod_links.as_ref().unwrap().get(name)
+                        else {
+                            panic!("Invalid previous mirror")
+                        };
+
+                        if prev_orig_windows.sha256 == windows.sha256
+                            && migrate(
+                                prev_windows,
                                 windows,
+                                prev_mods_dir,
                                 &mods_dir,
-                                format!("{base_name}-Win.zip"),
+                                prev_mods_url,
                                 &mods_url,
-                                &base_name,
-                            )?;
+                            )?
+                        {
+                            windows_ok = true;
                         }
-                        if !mac_ok {
-                            download_and_update(
-                                crate::AGENT.clone(),
+
+                        if prev_orig_mac.sha256 == mac.sha256
+                            && migrate(
+                                prev_mac,
                                 mac,
+                                prev_mods_dir,
                                 &mods_dir,
-                                format!("{base_name}-Mac.zip"),
+                                prev_mods_url,
                                 &mods_url,
-                                &base_name,
-                            )?;
+                            )?
+                        {
+                            mac_ok = true;
                         }
-                        if !linux_ok {
-                            download_and_update(
-                                crate::AGENT.clone(),
+
+                        if prev_orig_linux.sha256 == linux.sha256
+                            && migrate(
+                                prev_linux,
                                 linux,
+                                prev_mods_dir,
                                 &mods_dir,
-                                format!("{base_name}-Linux.zip"),
+                                prev_mods_url,
                                 &mods_url,
-                                &base_name,
-                            )?;
+                            )?
+                        {
+                            linux_ok = true;
                         }
                     }
-                };
 
-                Ok(())
-            })?;
+                    if !windows_ok {
+                        download_and_update(
+                            windows,
+                            cache.as_ref(),
+                            &staging,
+                            &mods_dir,
+                            format!("{base_name}-Win.zip"),
+                            &mods_url,
+                            &base_name,
+                        )?;
+                    }
+                    if !mac_ok {
+                        download_and_update(
+                            mac,
+                            cache.as_ref(),
+                            &staging,
+                            &mods_dir,
+                            format!("{base_name}-Mac.zip"),
+                            &mods_url,
+                            &base_name,
+                        )?;
+                    }
+                    if !linux_ok {
+                        download_and_update(
+                            linux,
+                            cache.as_ref(),
+                            &staging,
+                            &mods_dir,
+                            format!("{base_name}-Linux.zip"),
+                            &mods_url,
+                            &base_name,
+                        )?;
+                    }
+                }
+            };
+
+            Ok(())
+        });
+        staging.remove_if_empty();
+        result?;
 
         println!("Writing new ModLinks.xml");
         fs::write(base_dir.join("ModLinks.xml"), mod_links.to_xml()?)?;
@@ -263,8 +268,9 @@ fn migrate(
 }
 
 fn download_and_update(
-    agent: Agent,
     file: &mut FileDef,
+    cache: Option<&DownloadCache>,
+    staging: &Staging,
     mods_dir: impl AsRef<Path>,
     file_name: impl AsRef<str>,
     mods_url: &Url,
@@ -272,8 +278,17 @@ fn download_and_update(
 ) -> Result {
     let file_name = file_name.as_ref();
 
-    let zip = match download_and_zip(agent, file, fallback_name) {
-        Ok(zip) => zip,
+    let dest = mods_dir.as_ref().join(file_name);
+
+    let hash = match download_and_zip(
+        crate::AGENT.clone(),
+        file,
+        cache,
+        staging,
+        &dest,
+        fallback_name,
+    ) {
+        Ok(hash) => hash,
         Err(e) => match e.downcast_ref::<ureq::Error>() {
             Some(ureq::Error::Status(404, _)) => {
                 println!("File Not Found! Skipping");
@@ -282,9 +297,8 @@ fn download_and_update(
             _ => Err(e)?,
         },
     };
-    fs::write(mods_dir.as_ref().join(file_name), &zip)?;
 
-    file.sha256 = <Sha256 as Digest>::digest(zip).into();
+    file.sha256 = hash;
     file.url = mods_url.join(file_name)?;
 
     Ok(())
@@ -0,0 +1,49 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use hk_modlinks::{GraphFormat, GraphOptions};
+
+use super::changelog::write_output;
+use super::{InArgs, Run};
+use crate::Result;
+
+#[derive(Args, Debug, Clone)]
+pub struct Graph {
+    #[command(flatten)]
+    in_args: InArgs,
+
+    /// Only include these mods and their dependencies
+    #[arg(value_name = "MOD")]
+    roots: Vec<String>,
+
+    #[arg(short, long, value_name = "FILE")]
+    out: Option<PathBuf>,
+    /// Output format
+    #[arg(short, long, value_name = "FORMAT", default_value_t, value_enum)]
+    format: GraphFormat,
+    /// Do not include integration edges
+    #[arg(long)]
+    no_integrations: bool,
+    /// Color mods by their first tag
+    #[arg(long)]
+    color_by_tag: bool,
+}
+
+impl Run for Graph {
+    fn run(self) -> Result {
+        let mod_links = self.in_args.read()?;
+
+        let options = GraphOptions {
+            roots: self.roots,
+            integrations: !self.no_integrations,
+            color_by_tag: self.color_by_tag,
+        };
+
+        let graph = mod_links
+            .to_graph(self.format, &options)
+            .map_err(|e| e.to_string())?;
+
+        write_output(self.out, graph)
+    }
+}
@@ -0,0 +1,88 @@
+use std::fs;
+use std::path::PathBuf;
+
+use clap::{Args, ValueEnum};
+
+use itertools::Itertools;
+
+use hk_modlinks::{ChangelogRenderer, ModLinksHistory};
+
+use super::changelog::write_output;
+use super::{InArgs, Run};
+use crate::{Format, Result};
+
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum HistoryFormat {
+    #[default]
+    Markdown,
+    Json,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct History {
+    /// Modlinks snapshots from oldest to newest, or a single directory whose
+    /// modlinks files are ordered by file name. Snapshots are labelled by
+    /// their file stems.
+    #[arg(required = true, value_name = "FILE|DIR")]
+    snapshots: Vec<PathBuf>,
+
+    #[arg(short, long, value_name = "FILE")]
+    out: Option<PathBuf>,
+    /// Output format
+    #[arg(short, long, value_name = "FORMAT", default_value_t, value_enum)]
+    format: HistoryFormat,
+    /// Render with a custom Handlebars template instead of a built-in format
+    #[arg(short, long, value_name = "FILE", conflicts_with = "format")]
+    template: Option<PathBuf>,
+}
+
+impl Run for History {
+    fn run(self) -> Result {
+        let paths = match self.snapshots.as_slice() {
+            [dir] if dir.is_dir() => fs::read_dir(dir)?
+                .map_ok(|entry| entry.path())
+                .filter_ok(|path| path.is_file() && Format::from_path(path).is_ok())
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .sorted()
+                .collect_vec(),
+            _ => self.snapshots,
+        };
+
+        if paths.len() < 2 {
+            Err("At least two snapshots are required")?;
+        }
+
+        let snapshots = paths
+            .into_iter()
+            .map(|path| -> Result<_> {
+                let label = path
+                    .file_stem()
+                    .ok_or_else(|| format!("Invalid snapshot path: {}", path.display()))?
+                    .to_string_lossy()
+                    .into_owned();
+                Ok((label, InArgs::read_from_file(path)?))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let changelog = ModLinksHistory::new(
+            snapshots
+                .iter()
+                .map(|(label, mod_links)| (label.as_str(), mod_links)),
+        )
+        .changelog();
+
+        let output = match (self.template, self.format) {
+            (Some(path), _) => {
+                let mut renderer = ChangelogRenderer::new();
+                let name = path.display().to_string();
+                renderer.register_template_file(&name, &path)?;
+                renderer.render(&name, &changelog)?
+            }
+            (None, HistoryFormat::Markdown) => changelog.to_markdown()?,
+            (None, HistoryFormat::Json) => changelog.to_json()?,
+        };
+
+        write_output(self.out, output)
+    }
+}
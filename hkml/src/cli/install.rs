@@ -0,0 +1,313 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+
+use itertools::Itertools;
+
+use zip::ZipArchive;
+
+use hk_modlinks::{InstalledMod, ModLinks};
+
+use super::resolve::{read_mods_from_vec_or_file, ResolveArgs};
+use super::{finish_fetch, FetchArgs, InArgs, Run};
+use crate::{
+    mod_dir, mods_dir, parent_dir, temp_path_in, Installed, Result, Staging, VerifiedFile,
+};
+
+#[derive(Args, Debug, Clone)]
+pub struct GameArgs {
+    /// Hollow Knight installation directory, or its Managed directory
+    #[arg(short, long, value_name = "DIR", env = "HKML_GAME_DIR")]
+    game: PathBuf,
+}
+
+impl GameArgs {
+    #[inline]
+    pub fn mods_dir(&self) -> Result<PathBuf> {
+        mods_dir(&self.game)
+    }
+}
+
+/// Install mods in the given order into `Mods/<name>`, replacing mods
+/// installed from a different file. Directories not installed by hkml are
+/// skipped unless `force`.
+fn install(
+    fetch_args: &FetchArgs,
+    mod_links: &ModLinks,
+    mods_dir: &Path,
+    names: &[&str],
+    force: bool,
+) -> Result {
+    let platform = fetch_args.platform();
+    let mut installed = Installed::load(mods_dir)?;
+
+    let mut failed = vec![];
+    let mut pending = vec![];
+    let mut dests = vec![];
+    for &name in names {
+        let Some(info) = mod_links.get(name) else {
+            Err(format!("Unknown mod: {name}"))?
+        };
+        let file = info.links.file(Some(platform));
+        let dest = match mod_dir(mods_dir, name) {
+            Ok(dest) => dest,
+            Err(e) => {
+                failed.push((name, e));
+                continue;
+            }
+        };
+
+        let record = InstalledMod::new(info, file);
+        match installed.mods.get(name) {
+            Some(current) if *current == record => {
+                println!("{name} {} is up to date", current.version);
+            }
+            Some(current) if current.sha256 == file.sha256 => {
+                // Same file under another version, only the record changes
+                println!("Updated {name} {} -> {}", current.version, info.version);
+                installed.mods.insert(name.to_string(), record);
+                installed.save()?;
+            }
+            None if !force && dest.exists() => {
+                eprintln!(
+                    "Skipped {name}, {} was not installed by hkml",
+                    dest.display()
+                );
+            }
+            _ => {
+                pending.push((name, file));
+                dests.push(dest);
+            }
+        }
+    }
+
+    let cache = fetch_args.open_cache()?;
+    let cache = cache.as_ref();
+    // Next to Mods, so the game does not look into it
+    let staging = Staging::for_download(cache, parent_dir(mods_dir)?);
+    let fetched = fetch_args.fetch_files(&pending, cache, &staging);
+
+    for ((&(name, file), dest), result) in pending.iter().zip(dests).zip(fetched) {
+        let result = result
+            .and_then(|verified| install_mod(verified, &dest, name).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            failed.push((name, e));
+            continue;
+        }
+
+        let info = mod_links.get(name).unwrap();
+        match installed.mods.get(name) {
+            Some(old) => println!("Updated {name} {} -> {}", old.version, info.version),
+            None => println!("Installed {name} {}", info.version),
+        }
+
+        installed
+            .mods
+            .insert(name.to_string(), InstalledMod::new(info, file));
+        installed.save()?;
+    }
+
+    finish_fetch(&staging, &failed, "install")
+}
+
+/// Extract a mod into `dest` through a directory next to it, replacing what
+/// was there only once done.
+fn install_mod(verified: VerifiedFile, dest: &Path, fallback_name: &str) -> Result {
+    let tmp = temp_path_in(parent_dir(dest)?);
+
+    let result = extract_mod(verified, &tmp, fallback_name).and_then(|()| {
+        match fs::remove_dir_all(dest) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e)?,
+            _ => {}
+        }
+        fs::rename(&tmp, dest)?;
+
+        Ok(())
+    });
+
+    if result.is_err() {
+        fs::remove_dir_all(&tmp).ok();
+    }
+
+    result
+}
+
+fn extract_mod(verified: VerifiedFile, dir: &Path, fallback_name: &str) -> Result {
+    fs::create_dir(dir)?;
+
+    if verified.is_zip()? {
+        ZipArchive::new(verified.open()?)?.extract(dir)?;
+    } else {
+        let file_name = verified
+            .file_name
+            .clone()
+            .unwrap_or_else(|| format!("{fallback_name}.dll"));
+        verified.persist(&dir.join(file_name))?;
+    }
+
+    Ok(())
+}
+
+#[derive(Args, Debug, Clone)]
+#[group(id = "mod", required = true, multiple = false)]
+pub struct Install {
+    #[command(flatten)]
+    in_args: InArgs,
+    #[command(flatten)]
+    game_args: GameArgs,
+    /// Mods to be installed
+    #[arg(required = true, value_name = "MOD", group = "mod")]
+    mods: Option<Vec<String>>,
+    /// Read mods to be installed from file (one mod name per line, empty lines or lines starting with "#" are ignored)
+    #[arg(short = 'f', long = "file", value_name = "MODS FILE", group = "mod")]
+    mods_file: Option<PathBuf>,
+    /// Do not resolve dependencies
+    #[arg(long, conflicts_with_all = ["with_integrations", "exclude"])]
+    no_deps: bool,
+    #[command(flatten)]
+    resolve_args: ResolveArgs,
+    /// Replace directories of mods that were not installed by hkml
+    #[arg(long)]
+    force: bool,
+    #[command(flatten)]
+    fetch_args: FetchArgs,
+}
+
+impl Run for Install {
+    fn run(self) -> Result {
+        let mod_links = self.in_args.read()?;
+        let mods_dir = self.game_args.mods_dir()?;
+        let mods = read_mods_from_vec_or_file(self.mods, self.mods_file)?;
+
+        let names = if self.no_deps {
+            mods.iter().map(String::as_str).collect_vec()
+        } else {
+            self.resolve_args
+                .install_plan(&mod_links, &mods)?
+                .into_vec()
+        };
+
+        install(&self.fetch_args, &mod_links, &mods_dir, &names, self.force)
+    }
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct Update {
+    #[command(flatten)]
+    in_args: InArgs,
+    #[command(flatten)]
+    game_args: GameArgs,
+    /// Mods to be updated, defaults to all mods installed by hkml
+    #[arg(value_name = "MOD")]
+    mods: Vec<String>,
+    #[command(flatten)]
+    fetch_args: FetchArgs,
+}
+
+impl Run for Update {
+    fn run(self) -> Result {
+        let mod_links = self.in_args.read()?;
+        let mods_dir = self.game_args.mods_dir()?;
+        let installed = Installed::load(&mods_dir)?;
+
+        let mods = if self.mods.is_empty() {
+            installed.mods.mod_names().cloned().collect_vec()
+        } else {
+            if let Some(name) = self.mods.iter().find(|name| !installed.mods.contains(name)) {
+                Err(format!("{name} was not installed by hkml"))?;
+            }
+            self.mods
+        };
+
+        let (mods, missing): (Vec<_>, Vec<_>) = mods
+            .iter()
+            .map(String::as_str)
+            .partition(|name| mod_links.get(name).is_some());
+        for name in missing {
+            eprintln!("Skipped {name}, not in the modlinks");
+        }
+
+        // Each on its own, so a broken mod does not hold back the others
+        let mut resolved = vec![];
+        for name in mods {
+            match mod_links.resolve_deps_single(name) {
+                Ok(_) => resolved.push(name),
+                Err(e) => {
+                    for missing in e.names() {
+                        eprintln!("Skipped {name}, missing dependency {missing}");
+                    }
+                }
+            }
+        }
+
+        // New dependencies get installed along the way
+        let plan = mod_links
+            .install_plan(resolved)
+            .map_err(|e| e.to_string())?;
+
+        install(
+            &self.fetch_args,
+            &mod_links,
+            &mods_dir,
+            plan.as_slice(),
+            false,
+        )
+    }
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct Uninstall {
+    #[command(flatten)]
+    game_args: GameArgs,
+    /// Mods to be uninstalled
+    #[arg(required = true, value_name = "MOD")]
+    mods: Vec<String>,
+}
+
+impl Run for Uninstall {
+    fn run(self) -> Result {
+        let mods_dir = self.game_args.mods_dir()?;
+        let mut installed = Installed::load(&mods_dir)?;
+        let mods = self
+            .mods
+            .iter()
+            .map(String::as_str)
+            .collect::<BTreeSet<_>>();
+
+        for &name in &mods {
+            mod_dir(&mods_dir, name)?;
+            if !installed.mods.contains(name) {
+                Err(format!("{name} was not installed by hkml"))?;
+            }
+
+            let dependents = installed
+                .mods
+                .dependents(name)
+                .filter(|dependent| !mods.contains(dependent))
+                .collect_vec();
+            if !dependents.is_empty() {
+                Err(format!(
+                    "Cannot uninstall {name}, required by {}",
+                    dependents.join(", ")
+                ))?;
+            }
+        }
+
+        for name in mods {
+            match fs::remove_dir_all(mod_dir(&mods_dir, name)?) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e)?,
+                _ => {}
+            }
+
+            let removed = installed.mods.remove(name).unwrap();
+            installed.save()?;
+
+            println!("Uninstalled {name} {}", removed.version);
+        }
+
+        Ok(())
+    }
+}
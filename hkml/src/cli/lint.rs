@@ -0,0 +1,44 @@
+use std::fs;
+use std::path::PathBuf;
+
+use clap::Args;
+
+use hk_modlinks::{LintConfig, Linter, Severity};
+
+use super::validate::print_report;
+use super::{InArgs, Run};
+use crate::Result;
+
+#[derive(Args, Debug, Clone)]
+pub struct Lint {
+    #[command(flatten)]
+    in_args: InArgs,
+    /// TOML file configuring rules, see `hk_modlinks::LintConfig`
+    #[arg(short, long, value_name = "FILE")]
+    config: Option<PathBuf>,
+}
+
+impl Run for Lint {
+    fn run(self) -> Result {
+        let mut linter = Linter::new();
+
+        if let Some(path) = self.config {
+            let config: LintConfig = toml::from_str(&fs::read_to_string(&path)?)
+                .map_err(|e| format!("Invalid lint config {}: {e}", path.display()))?;
+            linter.configure(config)?;
+        }
+
+        let report = linter.lint(&self.in_args.read()?);
+
+        print_report(&report);
+
+        if report.has_errors() {
+            Err(format!(
+                "Lint failed with {} error(s)",
+                report.count(Severity::Error)
+            ))?;
+        }
+
+        Ok(())
+    }
+}
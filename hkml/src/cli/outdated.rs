@@ -0,0 +1,57 @@
+use clap::Args;
+
+use itertools::Itertools;
+
+use super::{GameArgs, InArgs, Run};
+use crate::{Installed, Result};
+
+#[derive(Args, Debug, Clone)]
+pub struct Outdated {
+    #[command(flatten)]
+    in_args: InArgs,
+    #[command(flatten)]
+    game_args: GameArgs,
+}
+
+impl Run for Outdated {
+    fn run(self) -> Result {
+        let mod_links = self.in_args.read()?;
+        let installed = Installed::load(&self.game_args.mods_dir()?)?;
+
+        let outdated = installed.mods.outdated(&mod_links);
+
+        for name in &outdated.missing {
+            eprintln!("Skipped {name}, not in the modlinks");
+        }
+        for missing in &outdated.missing_dependencies {
+            eprintln!("Skipped {}, missing dependency: {missing}", missing.root());
+        }
+
+        if outdated.is_empty() {
+            println!("{} mod(s) up to date", installed.mods.len());
+            return Ok(());
+        }
+
+        if !outdated.updates.is_empty() {
+            println!(
+                "Updates:\n{}",
+                outdated
+                    .updates
+                    .iter()
+                    .map(|update| format!(
+                        "{} {} -> {}",
+                        update.name, update.installed, update.available
+                    ))
+                    .join("\n")
+            );
+        }
+        if !outdated.new_dependencies.is_empty() {
+            println!(
+                "New dependencies:\n{}",
+                outdated.new_dependencies.iter().join("\n")
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -5,9 +5,50 @@ use clap::Args;
 
 use itertools::Itertools;
 
+use hk_modlinks::{InstallPlan, ModLinks, ResolveOptions};
+
 use super::{InArgs, Run};
 use crate::Result;
 
+#[derive(Args, Debug, Clone)]
+pub struct ResolveArgs {
+    /// Also resolve available mods that given mod(s) integrate with
+    #[arg(long)]
+    with_integrations: bool,
+    /// Also resolve integrations of integrations and dependencies
+    #[arg(long, requires = "with_integrations")]
+    transitive_integrations: bool,
+    /// Leave out mod and dependencies only it brings in, can be repeated
+    #[arg(long, value_name = "MOD")]
+    exclude: Vec<String>,
+}
+
+impl ResolveArgs {
+    /// Resolve mods in install order, reporting skipped integrations to
+    /// stderr.
+    pub fn install_plan<'a>(
+        self,
+        mod_links: &'a ModLinks,
+        mods: &'a [String],
+    ) -> Result<InstallPlan<'a>> {
+        let options = ResolveOptions {
+            integrations: self.with_integrations,
+            transitive_integrations: self.transitive_integrations,
+            excluded: self.exclude.into_iter().collect(),
+        };
+
+        let (plan, skipped) = mod_links
+            .install_plan_with(mods.iter().map(String::as_str), &options)
+            .map_err(|e| e.to_string())?;
+
+        for skipped in skipped {
+            eprintln!("Skipped {skipped}");
+        }
+
+        Ok(plan)
+    }
+}
+
 #[derive(Args, Debug, Clone)]
 #[group(id = "mod", required = true, multiple = false)]
 pub struct Resolve {
@@ -20,6 +61,8 @@ pub struct Resolve {
     /// Read mods to be resolved from file (one mod name per line, empty lines or lines starting with "#" are ignored)
     #[arg(short = 'f', long = "file", value_name = "MODS FILE", group = "mod")]
     mods_file: Option<PathBuf>,
+    #[command(flatten)]
+    resolve_args: ResolveArgs,
 }
 
 pub fn read_mods_from_vec_or_file(
@@ -43,10 +86,9 @@ impl Run for Resolve {
 
         println!(
             "{}",
-            mod_links
-                .resolve_deps(mods.iter().map(String::as_str))
-                .map_err(|u| format!("Unknown mods: {}", u.join(", ")))?
-                .into_iter()
+            self.resolve_args
+                .install_plan(&mod_links, &mods)?
+                .iter()
                 .join("\n")
         );
 
@@ -1,12 +1,26 @@
-use clap::Args;
+use std::fs;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
 
-use ureq::Agent;
+use clap::{Args, ValueEnum};
 
-use hk_modlinks::{FileDef, Links};
+use itertools::Itertools;
 
-use super::{InArgs, Run};
-use crate::cli::download_and_verify;
-use crate::Result;
+use hk_modlinks::{links_field, Severity, ValidationIssue, ValidationReport};
+
+use super::{CacheArgs, InArgs, Run};
+use crate::{verify_files, Result, Verification, VerificationCache, VerifyOutcome};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ReportFormat {
+    /// JUnit XML, with a test case for each mod
+    Junit,
+    /// SARIF 2.1.0, with a result for each issue
+    Sarif,
+    Json,
+}
 
 #[derive(Args, Debug, Clone)]
 pub struct Validate {
@@ -15,54 +29,136 @@ pub struct Validate {
     /// Skip validating hash
     #[arg(long)]
     no_hash: bool,
+    /// Number of files to download at a time when validating hash
+    #[arg(short, long, value_name = "N", default_value = "4")]
+    jobs: NonZeroUsize,
+    #[command(flatten)]
+    cache_args: CacheArgs,
+    /// Trust cached validations for this long before revalidating
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "1day",
+        value_parser = humantime::parse_duration,
+        requires = "cache_dir"
+    )]
+    max_age: Duration,
+    /// Also write the report to a file in this format
+    #[arg(long, value_name = "FORMAT", requires = "report_file")]
+    report_format: Option<ReportFormat>,
+    /// File to write the report to
+    #[arg(long, value_name = "FILE", requires = "report_format")]
+    report_file: Option<PathBuf>,
 }
 
 impl Run for Validate {
     fn run(self) -> Result {
+        let report_args = self.report_format.zip(self.report_file);
+        let artifact = self
+            .in_args
+            .r#in
+            .as_ref()
+            .map(|path| path.display().to_string());
         let mod_links = self.in_args.read()?;
 
-        mod_links
-            .validate_names()
-            .map_err(|m| format!("The following mod(s) has invalid name: {}", m.join(", ")))?;
+        let mut report = mod_links.validate();
+
+        if !self.no_hash {
+            let files = mod_links
+                .iter()
+                .flat_map(|(name, info)| {
+                    info.links
+                        .files()
+                        .into_iter()
+                        .map(|(platform, file)| Verification {
+                            mod_name: name,
+                            field: links_field(platform),
+                            file,
+                        })
+                })
+                .collect_vec();
+
+            let cache = self
+                .cache_args
+                .cache_dir()
+                .map(|dir| VerificationCache::load(dir, self.max_age))
+                .transpose()?
+                .map(Mutex::new);
+            let store = self.cache_args.open()?;
+
+            let results = verify_files(
+                &crate::AGENT,
+                files,
+                self.jobs,
+                cache.as_ref(),
+                store.as_ref(),
+            );
+
+            if let Some(cache) = cache {
+                cache.into_inner().unwrap().save()?;
 
-        mod_links.validate_relations().map_err(|m| {
-            format!(
-                "The following mod(s) contains non-existant mod in their relations: {}",
-                m.join(", ")
-            )
-        })?;
+                let count = |outcome| {
+                    results
+                        .iter()
+                        .filter(|(_, result)| result.as_ref().is_ok_and(|o| *o == outcome))
+                        .count()
+                };
+                eprintln!(
+                    "{} file(s) downloaded, {} not modified, {} cached",
+                    count(VerifyOutcome::Downloaded),
+                    count(VerifyOutcome::NotModified),
+                    count(VerifyOutcome::Cached)
+                );
+            }
 
-        if self.no_hash {
-            return Ok(());
+            for (verification, result) in results {
+                if let Err(e) = result {
+                    report.push(ValidationIssue::error(
+                        "file-verification",
+                        verification.mod_name,
+                        verification.field,
+                        e,
+                    ));
+                }
+            }
+            report.sort();
         }
 
-        for (name, info) in mod_links {
-            match info.links {
-                Links::Universal(file) => verify(crate::AGENT.clone(), &name, file, None)?,
-                Links::PlatformSpecific {
-                    windows,
-                    mac,
-                    linux,
-                } => {
-                    verify(crate::AGENT.clone(), &name, *windows, Some("Windows"))?;
-                    verify(crate::AGENT.clone(), &name, *mac, Some("Mac"))?;
-                    verify(crate::AGENT.clone(), &name, *linux, Some("Linux"))?;
+        print_report(&report);
+
+        if let Some((format, path)) = report_args {
+            let output = match format {
+                ReportFormat::Junit => {
+                    report.to_junit("hkml validate", mod_links.mod_names().map(String::as_str))?
                 }
+                ReportFormat::Sarif => {
+                    serde_json::to_string_pretty(&report.to_sarif("hkml", artifact.as_deref()))?
+                }
+                ReportFormat::Json => serde_json::to_string_pretty(&report.to_json())?,
             };
+            fs::write(path, output)?;
+        }
+
+        if report.has_errors() {
+            Err(format!(
+                "Validation failed with {} error(s)",
+                report.count(Severity::Error)
+            ))?;
         }
 
         Ok(())
     }
 }
 
-fn verify(agent: Agent, name: &String, file: FileDef, variant: Option<&'static str>) -> Result {
-    print!("Validating {name}");
-    match variant {
-        Some(variant) => println!(" ({variant})"),
-        None => println!(),
-    };
-
-    download_and_verify(agent, &file)?;
+pub(super) fn print_report(report: &ValidationReport) {
+    for issue in report {
+        println!("{issue}");
+    }
 
-    Ok(())
+    println!(
+        "{} error(s), {} warning(s), {} info",
+        report.count(Severity::Error),
+        report.count(Severity::Warning),
+        report.count(Severity::Info)
+    );
 }
@@ -0,0 +1,325 @@
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use actix_web::http::header::{ContentDisposition, HeaderValue, CONTENT_DISPOSITION};
+
+use serde::{Deserialize, Serialize};
+
+use ureq::Response;
+
+use url::Url;
+
+use hk_modlinks::FileDef;
+
+use crate::{
+    check_hash, hash_into, temp_path_in, unix_now, Result, TempFile, Validators, VerifiedFile,
+    TEMP_EXTENSION,
+};
+
+const BLOBS_DIR_NAME: &str = "blobs";
+const STAGING_DIR_NAME: &str = "partial";
+const INFO_EXTENSION: &str = "json";
+/// Partial writes older than this are assumed to be abandoned
+const STALE_TEMP_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// Metadata kept alongside a cached blob.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct BlobInfo {
+    pub sha256: String,
+    pub size: u64,
+    /// Name the file was originally served with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_name: Option<String>,
+    /// URL the file was last fetched from
+    pub url: String,
+    #[serde(flatten)]
+    pub validators: Validators,
+    /// Seconds since Unix epoch
+    pub last_used: u64,
+}
+
+/// Content-addressed store of downloaded files, keyed by SHA256.
+///
+/// Blobs are kept in `blobs/` of the cache directory, each with a JSON file
+/// of [`BlobInfo`]. Least recently used blobs are removed when the total size
+/// exceeds the limit. Unfinished downloads are kept in `partial/`.
+#[derive(Debug, Clone)]
+pub struct DownloadCache {
+    dir: PathBuf,
+    staging_dir: PathBuf,
+    max_size: u64,
+}
+
+impl DownloadCache {
+    pub fn open(dir: &Path, max_size: u64) -> Result<Self> {
+        let staging_dir = dir.join(STAGING_DIR_NAME);
+        let dir = dir.join(BLOBS_DIR_NAME);
+        fs::create_dir_all(&dir)?;
+
+        Ok(Self {
+            dir,
+            staging_dir,
+            max_size,
+        })
+    }
+
+    /// Directory to keep unfinished downloads in, on the same file system as
+    /// the blobs.
+    #[inline]
+    pub fn staging_dir(&self) -> &Path {
+        &self.staging_dir
+    }
+
+    fn blob_path(&self, sha256: &str) -> PathBuf {
+        self.dir.join(sha256)
+    }
+
+    fn info_path(&self, sha256: &str) -> PathBuf {
+        self.dir.join(sha256).with_extension(INFO_EXTENSION)
+    }
+
+    /// Metadata of the blob for the file, if cached.
+    pub fn info(&self, file: &FileDef) -> Option<BlobInfo> {
+        self.read_info(&file.sha256())
+    }
+
+    fn read_info(&self, sha256: &str) -> Option<BlobInfo> {
+        serde_json::from_slice(&fs::read(self.info_path(sha256)).ok()?).ok()
+    }
+
+    fn write_info(&self, info: &BlobInfo) -> Result {
+        let tmp = temp_path_in(&self.dir);
+        fs::write(&tmp, serde_json::to_vec_pretty(info)?)?;
+        fs::rename(tmp, self.info_path(&info.sha256))?;
+
+        Ok(())
+    }
+
+    /// Cached content of the file, after checking its hash. Blobs that do not
+    /// match are removed.
+    pub fn get(&self, file: &FileDef) -> Result<Option<VerifiedFile>> {
+        let sha256 = file.sha256();
+        let Some(mut info) = self.read_info(&sha256) else {
+            return Ok(None);
+        };
+
+        let path = self.blob_path(&sha256);
+        let blob = match File::open(&path) {
+            Ok(blob) => blob,
+            // Info is written before the blob is moved in place
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => Err(e)?,
+        };
+
+        let (_, hash, _) = hash_into(blob, io::sink())?;
+        if check_hash(file, &hash).is_err() {
+            eprintln!("Removing corrupted cached file {sha256}");
+            self.remove(&sha256)?;
+            return Ok(None);
+        }
+
+        info.last_used = unix_now();
+        self.write_info(&info)?;
+
+        Ok(Some(VerifiedFile::borrowed(path, info.file_name)))
+    }
+
+    /// Temporary file in the cache directory, to be committed once verified.
+    #[inline]
+    pub fn temp_file(&self) -> io::Result<TempFile> {
+        TempFile::new_in(&self.dir)
+    }
+
+    /// Move verified content of the file into the cache, then shrink the
+    /// cache to its size limit. Files larger than the limit are not cached.
+    pub fn commit(
+        &self,
+        verified: VerifiedFile,
+        file: &FileDef,
+        validators: Validators,
+    ) -> Result<VerifiedFile> {
+        let sha256 = file.sha256();
+        let path = self.blob_path(&sha256);
+        let file_name = verified.file_name.clone();
+        let size = verified.size()?;
+        if size > self.max_size {
+            return Ok(verified);
+        }
+
+        self.write_info(&BlobInfo {
+            sha256: sha256.clone(),
+            size,
+            file_name: file_name.clone(),
+            url: file.url.to_string(),
+            validators,
+            last_used: unix_now(),
+        })?;
+        verified.persist(&path)?;
+
+        self.shrink(self.max_size, Some(&sha256))?;
+
+        Ok(VerifiedFile::borrowed(path, file_name))
+    }
+
+    fn remove(&self, sha256: &str) -> Result {
+        for path in [self.blob_path(sha256), self.info_path(sha256)] {
+            match fs::remove_file(path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e)?,
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// All cached blobs, most recently used first.
+    pub fn entries(&self) -> Result<Vec<BlobInfo>> {
+        let mut entries = vec![];
+
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|x| x.to_str()) != Some(INFO_EXTENSION) {
+                continue;
+            }
+
+            let sha256 = path.file_stem().unwrap().to_string_lossy();
+            if let Some(info) = self.read_info(&sha256) {
+                if self.blob_path(&sha256).exists() {
+                    entries.push(info);
+                }
+            }
+        }
+
+        entries.sort_by(|a, b| {
+            b.last_used
+                .cmp(&a.last_used)
+                .then_with(|| a.sha256.cmp(&b.sha256))
+        });
+
+        Ok(entries)
+    }
+
+    /// Remove least recently used blobs until the total size is within
+    /// `max_size`, along with abandoned partial writes and downloads. Returns
+    /// removed blobs.
+    #[inline]
+    pub fn gc(&self, max_size: u64) -> Result<Vec<BlobInfo>> {
+        self.shrink(max_size, None)
+    }
+
+    /// Like [`DownloadCache::gc`], but never removing the blob `keep`.
+    fn shrink(&self, max_size: u64, keep: Option<&str>) -> Result<Vec<BlobInfo>> {
+        remove_stale(&self.dir, |path| {
+            path.extension().and_then(|x| x.to_str()) == Some(TEMP_EXTENSION)
+        })?;
+        remove_stale(&self.staging_dir, |_| true)?;
+
+        let mut entries = self.entries()?;
+        let mut size: u64 = entries.iter().map(|info| info.size).sum();
+        entries.retain(|info| Some(info.sha256.as_str()) != keep);
+        let mut removed = vec![];
+
+        while size > max_size {
+            let Some(info) = entries.pop() else {
+                break;
+            };
+
+            self.remove(&info.sha256)?;
+            size -= info.size;
+            removed.push(info);
+        }
+
+        Ok(removed)
+    }
+
+    /// Remove all blobs and unfinished downloads, returning how many blobs
+    /// were removed.
+    pub fn clear(&self) -> Result<usize> {
+        let count = self.entries()?.len();
+
+        fs::remove_dir_all(&self.dir)?;
+        fs::create_dir_all(&self.dir)?;
+        match fs::remove_dir_all(&self.staging_dir) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e)?,
+            _ => {}
+        }
+
+        Ok(count)
+    }
+}
+
+/// Remove files in `dir` matching `filter` that were last modified more than
+/// [`STALE_TEMP_AGE`] ago.
+fn remove_stale(dir: &Path, filter: impl Fn(&Path) -> bool) -> Result {
+    let entries = match fs::read_dir(dir) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        entries => entries?,
+    };
+
+    for entry in entries {
+        let entry = entry?;
+        let path = entry.path();
+        if !filter(&path) {
+            continue;
+        }
+
+        let age = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| SystemTime::now().duration_since(t).ok());
+        if age.is_some_and(|age| age > STALE_TEMP_AGE) {
+            fs::remove_file(path).ok();
+        }
+    }
+
+    Ok(())
+}
+
+/// Name of the downloaded file from `Content-Disposition`, or the last segment
+/// of the final URL.
+pub fn response_file_name(resp: &Response) -> Option<String> {
+    resp.header(CONTENT_DISPOSITION.as_str())
+        .and_then(|header| {
+            ContentDisposition::from_raw(
+                &HeaderValue::from_bytes(header.as_bytes())
+                    .expect("failed to perform identity transformation on header value"),
+            )
+            .unwrap()
+            .get_filename()
+            .map(ToOwned::to_owned)
+        })
+        .or_else(|| {
+            Url::parse(resp.get_url())
+                .unwrap()
+                .path_segments()
+                .and_then(|mut segments| segments.next_back().map(ToOwned::to_owned))
+        })
+}
+
+/// Parse a size such as `512MiB`, `1.5GB` or `1024`, in bytes.
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+
+    let number: f64 = number.parse().map_err(|_| format!("Invalid size: {s}"))?;
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "kb" => 1000,
+        "mb" => 1000_u64.pow(2),
+        "gb" => 1000_u64.pow(3),
+        "tb" => 1000_u64.pow(4),
+        "k" | "kib" => 1 << 10,
+        "m" | "mib" => 1 << 20,
+        "g" | "gib" => 1 << 30,
+        "t" | "tib" => 1 << 40,
+        unit => Err(format!("Unknown size unit: {unit}"))?,
+    };
+
+    Ok((number * multiplier as f64) as u64)
+}
@@ -0,0 +1,88 @@
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use hk_modlinks::{is_valid_mod_name, Installation};
+
+use crate::{temp_path_in, Result};
+
+const MODS_DIR_NAME: &str = "Mods";
+const INSTALLED_FILE_NAME: &str = "hkml-installed.json";
+
+/// Where the `Managed` directory may be found relative to a game directory.
+const MANAGED_DIRS: &[&str] = &[
+    "hollow_knight_Data/Managed",
+    "Hollow Knight_Data/Managed",
+    "hollow_knight.app/Contents/Resources/Data/Managed",
+    "Contents/Resources/Data/Managed",
+];
+
+/// Find the `Managed/Mods` directory of a Hollow Knight installation, creating
+/// `Mods` if missing. `game` may also be the `Managed` directory itself.
+pub fn mods_dir(game: &Path) -> Result<PathBuf> {
+    let managed = if game.ends_with("Managed") {
+        Some(game.to_path_buf())
+    } else {
+        MANAGED_DIRS
+            .iter()
+            .map(|dir| game.join(dir))
+            .find(|dir| dir.is_dir())
+    };
+
+    let Some(managed) = managed.filter(|dir| dir.is_dir()) else {
+        Err(format!(
+            "Cannot find Managed directory of Hollow Knight in {}",
+            game.display()
+        ))?
+    };
+
+    let mods_dir = managed.join(MODS_DIR_NAME);
+    fs::create_dir_all(&mods_dir)?;
+
+    Ok(mods_dir)
+}
+
+/// Directory of a mod inside `mods_dir`. Names come from modlinks and
+/// `hkml-installed.json` alike, so anything that could point elsewhere is
+/// rejected.
+pub fn mod_dir(mods_dir: &Path, name: &str) -> Result<PathBuf, String> {
+    let mut components = Path::new(name).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) if is_valid_mod_name(name) => Ok(mods_dir.join(name)),
+        _ => Err(format!("Invalid mod name: {name}")),
+    }
+}
+
+/// [`Installation`] of mods installed by hkml, kept inside the `Mods`
+/// directory.
+///
+/// Directories of mods not recorded here belong to the user and are never
+/// touched.
+#[derive(Debug, Clone)]
+pub struct Installed {
+    path: PathBuf,
+    pub mods: Installation,
+}
+
+impl Installed {
+    pub fn load(mods_dir: &Path) -> Result<Self> {
+        let path = mods_dir.join(INSTALLED_FILE_NAME);
+
+        let mods = match fs::read(&path) {
+            Ok(content) => serde_json::from_slice(&content)
+                .map_err(|e| format!("Invalid {}: {e}", path.display()))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Installation::new(),
+            Err(e) => Err(e)?,
+        };
+
+        Ok(Self { path, mods })
+    }
+
+    pub fn save(&self) -> Result {
+        let tmp = temp_path_in(self.path.parent().unwrap());
+        fs::write(&tmp, serde_json::to_vec_pretty(&self.mods)?)?;
+        fs::rename(tmp, &self.path)?;
+
+        Ok(())
+    }
+}
@@ -1,11 +1,20 @@
 mod cli;
+mod download_cache;
 mod format;
+mod installed;
+mod jobs;
 mod progress;
+mod retry;
+mod staging;
+mod verification_cache;
+mod verified_file;
+mod verify;
 
-#[cfg(not(any(target_os = "windows", target_os = "mac", target_os = "linux")))]
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
 compile_error!("This crate only supports Windows, Mac OS or Linux");
 
 use std::error::Error;
+use std::process::ExitCode;
 
 use actix_web::http::header::{ACCEPT, CACHE_CONTROL, CONNECTION};
 
@@ -13,11 +22,19 @@ use clap::Parser;
 
 use lazy_static::lazy_static;
 
-use ureq::{Agent, MiddlewareNext, Request};
+use ureq::{Agent, MiddlewareNext, Request, Response};
 
 use cli::*;
+use download_cache::*;
 use format::*;
+use installed::*;
+use jobs::*;
 use progress::*;
+use retry::*;
+use staging::*;
+use verification_cache::*;
+use verified_file::*;
+use verify::*;
 
 type Result<T = (), E = Box<dyn Error>> = std::result::Result<T, E>;
 
@@ -28,20 +45,31 @@ const MODLINKS_DEFAULT_CAPACITY: usize = 3 * 128 * 1024;
 lazy_static! {
     static ref AGENT: Agent = ureq::builder()
         .user_agent(USER_AGENT)
-        .middleware(|request: Request, next: MiddlewareNext<'_>| {
-            next.handle(
-                request
-                    .set(CONNECTION.as_str(), "keep-alive")
-                    .set(CACHE_CONTROL.as_str(), "no-cache, no-store")
-                    .set(
-                        ACCEPT.as_str(),
-                        "application/octet-stream, application/zip, application/x-msdownload",
-                    ),
-            )
-        })
+        .middleware(default_headers)
         .build();
 }
 
-fn main() -> Result {
-    Cli::parse().run()
+// The error type is dictated by ureq
+#[allow(clippy::result_large_err)]
+fn default_headers(request: Request, next: MiddlewareNext<'_>) -> Result<Response, ureq::Error> {
+    next.handle(
+        request
+            .set(CONNECTION.as_str(), "keep-alive")
+            .set(CACHE_CONTROL.as_str(), "no-cache, no-store")
+            .set(
+                ACCEPT.as_str(),
+                "application/octet-stream, application/zip, application/x-msdownload",
+            ),
+    )
+}
+
+fn main() -> ExitCode {
+    match Cli::parse().run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            // Display rather than Debug keeps multi-line errors readable
+            eprintln!("Error: {e}");
+            ExitCode::FAILURE
+        }
+    }
 }
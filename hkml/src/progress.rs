@@ -8,6 +8,7 @@ use crate::{Result, DEFAULT_BUF_SIZE};
 
 const PROGRESS_BAR_TEMPLATE: &str =
     "{prefix:>16.cyan.bold} [{elapsed_precise}] [{bar:40}] {spinner} {percent:>2}% ({total_bytes:9}) {binary_bytes_per_sec:>11.green.bold} ETA: {eta} {msg}";
+const COUNT_PROGRESS_BAR_TEMPLATE: &str = "{prefix:>16.cyan.bold} [{bar:40}] {pos}/{len}";
 const SPINNER_TEMPLATE: &str =
     "{prefix:>16.cyan.bold} [{elapsed_precise}] {spinner} {binary_bytes_per_sec:>11.green.bold} {msg}";
 
@@ -16,11 +17,32 @@ lazy_static! {
         .template(PROGRESS_BAR_TEMPLATE)
         .unwrap()
         .progress_chars("=> ");
+    static ref COUNT_PROGRESS_BAR_STYLE: ProgressStyle = ProgressStyle::default_bar()
+        .template(COUNT_PROGRESS_BAR_TEMPLATE)
+        .unwrap()
+        .progress_chars("=> ");
     static ref SPINNER_STYLE: ProgressStyle = ProgressStyle::default_spinner()
         .template(SPINNER_TEMPLATE)
         .unwrap();
 }
 
+/// Progress bar for transferring a file of optionally known size, styled like
+/// the ones used when copying.
+pub fn file_progress_bar(size: Option<u64>, prefix: String) -> ProgressBar {
+    match size {
+        Some(size) => ProgressBar::new(size).with_style(PROGRESS_BAR_STYLE.clone()),
+        None => ProgressBar::new_spinner().with_style(SPINNER_STYLE.clone()),
+    }
+    .with_prefix(prefix)
+}
+
+/// Progress bar counting finished items out of `len`.
+pub fn count_progress_bar(len: usize, prefix: &'static str) -> ProgressBar {
+    ProgressBar::new(len as u64)
+        .with_style(COUNT_PROGRESS_BAR_STYLE.clone())
+        .with_prefix(prefix)
+}
+
 pub fn copy_pb_buf_read<R: Read, W: Write>(
     r: &mut R,
     w: &mut W,
@@ -43,9 +65,8 @@ pub fn copy_pb_buf_read<R: Read, W: Write>(
                         )))
                     }
                 })
-                .map_err(|e| {
+                .inspect_err(|_| {
                     pb.abandon_with_message(format!("Failed when {action}"));
-                    e
                 })?;
         }
         None => {
@@ -53,9 +74,8 @@ pub fn copy_pb_buf_read<R: Read, W: Write>(
                 .with_style(SPINNER_STYLE.clone())
                 .with_prefix(action);
 
-            copy_pb_buf_read_inner(r, w, &pb).map_err(|e| {
+            copy_pb_buf_read_inner(r, w, &pb).inspect_err(|_| {
                 pb.abandon_with_message(format!("Failed when {action}"));
-                e
             })?;
         }
     }
@@ -63,57 +83,6 @@ pub fn copy_pb_buf_read<R: Read, W: Write>(
     Ok(())
 }
 
-pub fn copy_pb_slice<W: Write>(slice: &[u8], w: &mut W, action: &'static str) -> Result {
-    let size = slice.len();
-    let pb = ProgressBar::new(size as u64)
-        .with_style(PROGRESS_BAR_STYLE.clone())
-        .with_prefix(action);
-
-    let mut bytes_written = 0;
-
-    // Write chunks until size exceeds
-    if size > DEFAULT_BUF_SIZE {
-        loop {
-            match w.write(&slice[bytes_written..bytes_written + DEFAULT_BUF_SIZE]) {
-                Ok(0) => Err(io::Error::other(format!("Failed to write when {action}")))?,
-                Ok(n) => {
-                    bytes_written += n;
-                    pb.inc(n as u64);
-
-                    if bytes_written + DEFAULT_BUF_SIZE > size {
-                        break;
-                    }
-                }
-                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
-                Err(e) => Err(e)?,
-            }
-        }
-    }
-
-    // Write the rest
-    if bytes_written < size {
-        loop {
-            match w.write(&slice[bytes_written..]) {
-                Ok(0) => Err(io::Error::other(format!(
-                    "Failed to write last part when {action}"
-                )))?,
-                Ok(n) => {
-                    bytes_written += n;
-                    pb.inc(n as u64);
-
-                    if bytes_written >= size {
-                        break;
-                    }
-                }
-                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
-                Err(e) => Err(e)?,
-            }
-        }
-    }
-
-    Ok(())
-}
-
 fn copy_pb_buf_read_inner<R: Read, W: Write>(
     r: &mut R,
     w: &mut W,
@@ -0,0 +1,83 @@
+use std::error::Error;
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+use crate::Result;
+
+const DEFAULT_INITIAL_DELAY: Duration = Duration::from_millis(500);
+
+/// Retrying of transient failures with exponential backoff.
+#[derive(Debug, Clone, Copy)]
+pub struct Retry {
+    /// Attempts after the first one
+    pub retries: u32,
+    /// Delay before the first retry, doubled for each retry after
+    pub initial_delay: Duration,
+}
+
+impl Retry {
+    #[inline]
+    #[must_use]
+    pub fn new(retries: u32) -> Self {
+        Self {
+            retries,
+            initial_delay: DEFAULT_INITIAL_DELAY,
+        }
+    }
+
+    /// Call `f` until it succeeds, fails with an error that is not
+    /// [retryable](is_retryable), or runs out of retries. `on_retry` is called
+    /// with the error and delay before each retry.
+    pub fn run<T>(
+        &self,
+        mut f: impl FnMut() -> Result<T>,
+        mut on_retry: impl FnMut(&dyn Error, Duration),
+    ) -> Result<T> {
+        let mut delay = self.initial_delay;
+
+        for _ in 0..self.retries {
+            match f() {
+                Err(e) if is_retryable(e.as_ref()) => {
+                    on_retry(e.as_ref(), delay);
+                    thread::sleep(delay);
+                    delay *= 2;
+                }
+                result => return result,
+            }
+        }
+
+        f()
+    }
+}
+
+/// Whether the error may go away by trying again, such as server errors, rate
+/// limiting and dropped connections.
+pub fn is_retryable(e: &(dyn Error + 'static)) -> bool {
+    if let Some(e) = e.downcast_ref::<ureq::Error>() {
+        return match e {
+            ureq::Error::Status(status, _) => matches!(status, 408 | 429 | 500 | 502 | 503 | 504),
+            ureq::Error::Transport(transport) => !matches!(
+                transport.kind(),
+                ureq::ErrorKind::InvalidUrl
+                    | ureq::ErrorKind::UnknownScheme
+                    | ureq::ErrorKind::InvalidProxyUrl
+                    | ureq::ErrorKind::BadHeader
+            ),
+        };
+    }
+
+    if let Some(e) = e.downcast_ref::<io::Error>() {
+        return matches!(
+            e.kind(),
+            io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::BrokenPipe
+                | io::ErrorKind::TimedOut
+                | io::ErrorKind::UnexpectedEof
+                | io::ErrorKind::Interrupted
+        );
+    }
+
+    false
+}
@@ -0,0 +1,291 @@
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use actix_web::http::header::{
+    ACCEPT_ENCODING, CONTENT_LENGTH, CONTENT_RANGE, ETAG, IF_RANGE, LAST_MODIFIED, RANGE,
+};
+
+use indicatif::MultiProgress;
+
+use serde::{Deserialize, Serialize};
+
+use sha2::{Digest, Sha256};
+
+use ureq::{Agent, Response};
+
+use hk_modlinks::FileDef;
+
+use crate::{
+    check_hash, copy_hashing, file_progress_bar, response_file_name, temp_path_in, DownloadCache,
+    Result, Validators, VerifiedFile, TEMP_EXTENSION,
+};
+
+const STAGING_DIR_NAME: &str = ".hkml-partial";
+const INFO_EXTENSION: &str = "json";
+
+/// What an unfinished download was fetched as, kept next to its content.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+struct PartialInfo {
+    url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    file_name: Option<String>,
+    #[serde(flatten)]
+    validators: Validators,
+}
+
+impl PartialInfo {
+    /// Validator for `If-Range`, which must be a strong ETag or a date.
+    fn if_range(&self) -> Option<&str> {
+        self.validators
+            .etag
+            .as_deref()
+            .filter(|etag| !etag.starts_with("W/"))
+            .or(self.validators.last_modified.as_deref())
+    }
+}
+
+/// Downloaded content and what the server sent along with it.
+struct Fetched {
+    hash: [u8; 32],
+    info: PartialInfo,
+    resumed: bool,
+}
+
+/// Directory keeping unfinished downloads, so they can be resumed with range
+/// requests after an interruption.
+///
+/// Each download is kept as `<SHA256>.part` with a JSON file recording where
+/// it came from. Content is checked against the full hash once finished.
+#[derive(Debug)]
+pub struct Staging {
+    dir: PathBuf,
+    /// Hashes of files being downloaded right now
+    active: Mutex<HashSet<String>>,
+}
+
+impl Staging {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            active: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Staging of the cache if there is one, or a hidden directory in `dir`
+    /// otherwise.
+    pub fn for_download(cache: Option<&DownloadCache>, dir: &Path) -> Self {
+        Self::new(match cache {
+            Some(cache) => cache.staging_dir().to_path_buf(),
+            None => dir.join(STAGING_DIR_NAME),
+        })
+    }
+
+    /// Remove the directory if nothing is left to resume.
+    pub fn remove_if_empty(&self) {
+        fs::remove_dir(&self.dir).ok();
+    }
+
+    /// Download a file while checking its hash, resuming what is left of a
+    /// previous attempt if the server supports it.
+    ///
+    /// A resumed download that does not match is downloaded again in full.
+    pub fn download(
+        &self,
+        agent: &Agent,
+        file: &FileDef,
+        multi: &MultiProgress,
+        label: String,
+    ) -> Result<(VerifiedFile, Validators)> {
+        fs::create_dir_all(&self.dir)?;
+
+        let sha256 = file.sha256();
+        if !self.active.lock().unwrap().insert(sha256.clone()) {
+            // Same file downloaded by another job, which owns the partial file
+            let path = temp_path_in(&self.dir);
+            let result = self.download_to(agent, file, &path, None, multi, label);
+            if result.is_err() {
+                fs::remove_file(&path).ok();
+            }
+            return result;
+        }
+
+        let part = self.dir.join(format!("{sha256}.{TEMP_EXTENSION}"));
+        let info_path = self.dir.join(format!("{sha256}.{INFO_EXTENSION}"));
+        let result = self.download_to(agent, file, &part, Some(&info_path), multi, label);
+
+        self.active.lock().unwrap().remove(&sha256);
+
+        result
+    }
+
+    fn download_to(
+        &self,
+        agent: &Agent,
+        file: &FileDef,
+        part: &Path,
+        info_path: Option<&Path>,
+        multi: &MultiProgress,
+        label: String,
+    ) -> Result<(VerifiedFile, Validators)> {
+        let partial = info_path
+            .and_then(read_info)
+            .filter(|info| info.url == file.url.as_str());
+
+        let mut fetched = fetch(agent, file, part, info_path, partial, multi, &label)?;
+        if fetched.hash != file.sha256 && fetched.resumed {
+            multi
+                .println(format!(
+                    "Resumed download of {label} does not match, downloading again"
+                ))
+                .ok();
+            fetched = fetch(agent, file, part, info_path, None, multi, &label)?;
+        }
+
+        if let Err(e) = check_hash(file, &fetched.hash) {
+            discard(part, info_path);
+            Err(e)?;
+        }
+
+        if let Some(info_path) = info_path {
+            fs::remove_file(info_path).ok();
+        }
+
+        // Moved out of the way, so the file can be downloaded again meanwhile
+        let path = temp_path_in(&self.dir);
+        fs::rename(part, &path)?;
+
+        Ok((
+            VerifiedFile::temporary(path, fetched.info.file_name),
+            fetched.info.validators,
+        ))
+    }
+}
+
+fn read_info(path: &Path) -> Option<PartialInfo> {
+    serde_json::from_slice(&fs::read(path).ok()?).ok()
+}
+
+fn discard(part: &Path, info_path: Option<&Path>) {
+    fs::remove_file(part).ok();
+    if let Some(info_path) = info_path {
+        fs::remove_file(info_path).ok();
+    }
+}
+
+/// Start of the range in a `Content-Range` header like `bytes 100-199/200`.
+fn content_range_start(resp: &Response) -> Option<u64> {
+    resp.header(CONTENT_RANGE.as_str())?
+        .strip_prefix("bytes ")?
+        .split_once('-')?
+        .0
+        .parse()
+        .ok()
+}
+
+/// Fetch the file into `part`, continuing after what is there if `partial`
+/// is given and the server responds with the rest of it. What was fetched is
+/// kept on failure, so it can be resumed again.
+fn fetch(
+    agent: &Agent,
+    file: &FileDef,
+    part: &Path,
+    info_path: Option<&Path>,
+    partial: Option<PartialInfo>,
+    multi: &MultiProgress,
+    label: &str,
+) -> Result<Fetched> {
+    let offset = match &partial {
+        Some(_) => fs::metadata(part).map_or(0, |meta| meta.len()),
+        None => 0,
+    };
+    let partial = partial.filter(|_| offset > 0);
+
+    // Ranges and Content-Length count bytes as sent, which must be what is
+    // written to the partial file
+    let mut req = agent
+        .get(file.url.as_str())
+        .set(ACCEPT_ENCODING.as_str(), "identity");
+    if let Some(partial) = &partial {
+        req = req.set(RANGE.as_str(), &format!("bytes={offset}-"));
+        if let Some(validator) = partial.if_range() {
+            req = req.set(IF_RANGE.as_str(), validator);
+        }
+    }
+
+    let resp = match req.call() {
+        // Partial file is not shorter than the file anymore
+        Err(ureq::Error::Status(416, _)) if partial.is_some() => {
+            discard(part, info_path);
+            return fetch(agent, file, part, info_path, None, multi, label);
+        }
+        resp => resp?,
+    };
+
+    let start = match resp.status() {
+        206 if partial.is_none() => Err(format!("Unexpected partial content from {}", file.url))?,
+        206 if content_range_start(&resp) != Some(offset) => {
+            discard(part, info_path);
+            return fetch(agent, file, part, info_path, None, multi, label);
+        }
+        206 => offset,
+        _ => 0,
+    };
+
+    let fallback = partial.unwrap_or_default();
+    let validators = Validators {
+        etag: resp.header(ETAG.as_str()).map(str::to_string),
+        last_modified: resp.header(LAST_MODIFIED.as_str()).map(str::to_string),
+    };
+    let info = PartialInfo {
+        url: file.url.to_string(),
+        file_name: response_file_name(&resp).or(fallback.file_name),
+        validators: if validators.is_empty() {
+            fallback.validators
+        } else {
+            validators
+        },
+    };
+
+    let mut hasher = <Sha256 as Digest>::new();
+    let out = if start > 0 {
+        let mut out = File::options().read(true).append(true).open(part)?;
+        copy_hashing(&mut out, io::sink(), &mut hasher)?;
+        out
+    } else {
+        // Recorded first, so whatever gets written can be resumed
+        if let Some(info_path) = info_path {
+            fs::write(info_path, serde_json::to_vec_pretty(&info)?)?;
+        }
+        File::create(part)?
+    };
+
+    let len = resp
+        .header(CONTENT_LENGTH.as_str())
+        .and_then(|i| i.parse::<u64>().ok());
+    let pb = multi.add(file_progress_bar(
+        len.map(|len| start + len),
+        label.to_string(),
+    ));
+    pb.set_position(start);
+
+    let copied = copy_hashing(pb.wrap_read(resp.into_reader()), out, &mut hasher);
+    multi.remove(&pb);
+    let (out, copied) = copied?;
+    out.sync_all()?;
+
+    if len.is_some_and(|len| len != copied) {
+        Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "Connection closed before download finished",
+        ))?;
+    }
+
+    Ok(Fetched {
+        hash: hasher.finalize().into(),
+        info,
+        resumed: start > 0,
+    })
+}
@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use hk_modlinks::FileDef;
+
+use crate::Result;
+
+const VERIFICATION_CACHE_FILE_NAME: &str = "verified.json";
+
+/// Validators of a verified download for conditional requests.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Validators {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+struct Entry {
+    url: String,
+    sha256: String,
+    #[serde(flatten)]
+    validators: Validators,
+    /// Seconds since Unix epoch
+    verified_at: u64,
+}
+
+/// What is known about a file from previous verifications.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CachedVerification {
+    /// Verified within max age, no need to check again.
+    Fresh,
+    /// Verified before, revalidate with a conditional request if possible.
+    Stale(Validators),
+    Unknown,
+}
+
+/// Successful verifications keyed by URL and expected SHA256, persisted as
+/// JSON in the cache directory.
+#[derive(Debug, Clone)]
+pub struct VerificationCache {
+    path: PathBuf,
+    max_age: Duration,
+    entries: BTreeMap<String, Entry>,
+}
+
+impl VerificationCache {
+    /// Load the cache from the directory, starting empty if there is none or
+    /// it cannot be read.
+    pub fn load(dir: &Path, max_age: Duration) -> Result<Self> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(VERIFICATION_CACHE_FILE_NAME);
+
+        let entries = match fs::read(&path) {
+            Ok(buf) => serde_json::from_slice(&buf).unwrap_or_else(|e| {
+                eprintln!(
+                    "Ignoring invalid verification cache {}: {e}",
+                    path.display()
+                );
+                Default::default()
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Default::default(),
+            Err(e) => Err(e)?,
+        };
+
+        Ok(Self {
+            path,
+            max_age,
+            entries,
+        })
+    }
+
+    /// Remove the cache file from the directory if there is one.
+    pub fn clear(dir: &Path) -> Result {
+        match fs::remove_file(dir.join(VERIFICATION_CACHE_FILE_NAME)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e)?,
+            _ => Ok(()),
+        }
+    }
+
+    pub fn save(&self) -> Result {
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(&self.entries)?)?;
+        fs::rename(tmp, &self.path)?;
+
+        Ok(())
+    }
+
+    fn key(file: &FileDef) -> String {
+        format!("{} {}", file.sha256(), file.url)
+    }
+
+    pub fn get(&self, file: &FileDef) -> CachedVerification {
+        match self.entries.get(&Self::key(file)) {
+            Some(entry)
+                if unix_now().saturating_sub(entry.verified_at) < self.max_age.as_secs() =>
+            {
+                CachedVerification::Fresh
+            }
+            Some(entry) => CachedVerification::Stale(entry.validators.clone()),
+            None => CachedVerification::Unknown,
+        }
+    }
+
+    pub fn insert(&mut self, file: &FileDef, validators: Validators) {
+        self.entries.insert(
+            Self::key(file),
+            Entry {
+                url: file.url.to_string(),
+                sha256: file.sha256(),
+                validators,
+                verified_at: unix_now(),
+            },
+        );
+    }
+
+    pub fn remove(&mut self, file: &FileDef) {
+        self.entries.remove(&Self::key(file));
+    }
+}
+
+/// Seconds since Unix epoch
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
@@ -0,0 +1,212 @@
+use std::fs::{self, File};
+use std::io::{self, prelude::*};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use sha2::{Digest, Sha256};
+
+use hk_modlinks::FileDef;
+
+use crate::{Result, DEFAULT_BUF_SIZE};
+
+pub const TEMP_EXTENSION: &str = "part";
+
+/// Unique path in the directory to write to before moving into place.
+pub fn temp_path_in(dir: &Path) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    dir.join(format!(
+        ".hkml-{}-{}.{TEMP_EXTENSION}",
+        process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// Directory containing `path`, which does not exist for a root or an empty
+/// path.
+pub fn parent_dir(path: &Path) -> Result<&Path> {
+    Ok(path
+        .parent()
+        .ok_or_else(|| format!("Invalid path: {}", path.display()))?)
+}
+
+/// Copy everything from `r` to `w`, returning the writer with the SHA256 and
+/// size of what was copied.
+pub fn hash_into<R: Read, W: Write>(r: R, w: W) -> io::Result<(W, [u8; 32], u64)> {
+    let mut hasher = <Sha256 as Digest>::new();
+    let (w, size) = copy_hashing(r, w, &mut hasher)?;
+
+    Ok((w, hasher.finalize().into(), size))
+}
+
+/// Copy everything from `r` to `w`, feeding it to `hasher` as well. Returns
+/// the writer and size of what was copied.
+pub fn copy_hashing<R: Read, W: Write>(
+    mut r: R,
+    mut w: W,
+    hasher: &mut Sha256,
+) -> io::Result<(W, u64)> {
+    let mut buf = vec![0; DEFAULT_BUF_SIZE];
+    let mut size = 0;
+
+    loop {
+        let n = match r.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+
+        w.write_all(&buf[..n])?;
+        hasher.update(&buf[..n]);
+        size += n as u64;
+    }
+
+    w.flush()?;
+
+    Ok((w, size))
+}
+
+pub fn check_hash(file: &FileDef, hash: &[u8; 32]) -> Result<(), String> {
+    if *hash != file.sha256 {
+        Err(format!(
+            "Hash mismatch, expected {} but got {}",
+            hex::encode_upper(file.sha256),
+            hex::encode_upper(hash)
+        ))?;
+    }
+
+    Ok(())
+}
+
+/// File being written, removed if dropped before it is verified.
+#[derive(Debug)]
+pub struct TempFile {
+    path: PathBuf,
+    file: Option<File>,
+}
+
+impl TempFile {
+    pub fn new_in(dir: &Path) -> io::Result<Self> {
+        let path = temp_path_in(dir);
+        let file = File::create(&path)?;
+
+        Ok(Self {
+            path,
+            file: Some(file),
+        })
+    }
+
+    /// Keep the file as verified content, flushed to disk.
+    pub fn into_verified(mut self, file_name: Option<String>) -> io::Result<VerifiedFile> {
+        self.file.take().unwrap().sync_all()?;
+
+        Ok(VerifiedFile {
+            path: self.path.clone(),
+            temporary: true,
+            file_name,
+        })
+    }
+}
+
+impl Write for TempFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.as_mut().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.as_mut().unwrap().flush()
+    }
+}
+
+impl Seek for TempFile {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.file.as_mut().unwrap().seek(pos)
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if self.file.take().is_some() {
+            fs::remove_file(&self.path).ok();
+        }
+    }
+}
+
+/// Downloaded file on disk whose content matched the expected hash.
+///
+/// A temporary file is removed when dropped, unless persisted. Otherwise it is
+/// owned by someone else, such as the download cache, and left alone.
+#[derive(Debug)]
+pub struct VerifiedFile {
+    path: PathBuf,
+    temporary: bool,
+    /// Name the file was served with
+    pub file_name: Option<String>,
+}
+
+impl VerifiedFile {
+    /// Verified file to remove unless persisted.
+    #[inline]
+    pub fn temporary(path: PathBuf, file_name: Option<String>) -> Self {
+        Self {
+            path,
+            temporary: true,
+            file_name,
+        }
+    }
+
+    /// File owned by someone else, already verified.
+    #[inline]
+    pub fn borrowed(path: PathBuf, file_name: Option<String>) -> Self {
+        Self {
+            path,
+            temporary: false,
+            file_name,
+        }
+    }
+
+    #[inline]
+    pub fn open(&self) -> io::Result<File> {
+        File::open(&self.path)
+    }
+
+    pub fn size(&self) -> io::Result<u64> {
+        Ok(fs::metadata(&self.path)?.len())
+    }
+
+    pub fn is_zip(&self) -> io::Result<bool> {
+        let mut head = Vec::with_capacity(8);
+        self.open()?.take(8).read_to_end(&mut head)?;
+
+        Ok(infer::archive::is_zip(&head))
+    }
+
+    /// Move the file to `dest`, replacing it atomically. Files that are not
+    /// temporary are copied instead.
+    pub fn persist(mut self, dest: &Path) -> io::Result<()> {
+        if self.temporary && fs::rename(&self.path, dest).is_ok() {
+            self.temporary = false;
+            return Ok(());
+        }
+
+        // Copy next to the destination, so it can still be renamed into place
+        let mut tmp = TempFile::new_in(dest.parent().unwrap_or(Path::new("")))?;
+        io::copy(&mut self.open()?, &mut tmp)?;
+
+        let mut copy = tmp.into_verified(None)?;
+        fs::rename(&copy.path, dest)?;
+        copy.temporary = false;
+
+        Ok(())
+    }
+}
+
+impl Drop for VerifiedFile {
+    fn drop(&mut self) {
+        if self.temporary {
+            fs::remove_file(&self.path).ok();
+        }
+    }
+}
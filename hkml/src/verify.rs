@@ -0,0 +1,189 @@
+use std::io;
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+
+use actix_web::http::header::{
+    CONTENT_LENGTH, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+
+use indicatif::MultiProgress;
+
+use ureq::Agent;
+
+use hk_modlinks::FileDef;
+
+use crate::{
+    check_hash, count_progress_bar, file_progress_bar, hash_into, map_concurrent,
+    response_file_name, CachedVerification, DownloadCache, Validators, VerificationCache,
+};
+
+/// A file to verify, labelled by the mod and field it belongs to.
+#[derive(Debug, Clone)]
+pub struct Verification<'a> {
+    pub mod_name: &'a str,
+    pub field: String,
+    pub file: &'a FileDef,
+}
+
+impl Verification<'_> {
+    fn label(&self) -> String {
+        format!("{} ({})", self.mod_name, self.field)
+    }
+}
+
+/// How a file was found to be valid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyOutcome {
+    Downloaded,
+    /// Unchanged since cached verification according to the server.
+    NotModified,
+    /// Verified recently enough to be trusted without a request.
+    Cached,
+}
+
+/// Verify files with at most `jobs` downloads at a time, skipping or
+/// revalidating files verified before if a cache is given.
+///
+/// Downloaded files are added to the store if given, and files already in it
+/// are revalidated with the validators they were stored with.
+///
+/// Results are in the same order as the files, and failures do not stop other
+/// files from being verified.
+pub fn verify_files<'a>(
+    agent: &Agent,
+    files: Vec<Verification<'a>>,
+    jobs: NonZeroUsize,
+    cache: Option<&Mutex<VerificationCache>>,
+    store: Option<&DownloadCache>,
+) -> Vec<(Verification<'a>, Result<VerifyOutcome, String>)> {
+    let multi = MultiProgress::new();
+    let overall = multi.add(count_progress_bar(files.len(), "Verifying"));
+
+    let results = map_concurrent(&files, jobs, |verification| {
+        let file = verification.file;
+        let cached = cache.map_or(CachedVerification::Unknown, |cache| {
+            cache.lock().unwrap().get(file)
+        });
+
+        let validators = match cached {
+            CachedVerification::Fresh => None,
+            CachedVerification::Stale(validators) => Some(validators),
+            CachedVerification::Unknown => Some(
+                store
+                    .and_then(|store| store.info(file))
+                    .filter(|info| info.url == file.url.as_str())
+                    .map(|info| info.validators)
+                    .unwrap_or_default(),
+            ),
+        };
+
+        let result = match validators {
+            None => Ok(VerifyOutcome::Cached),
+            Some(validators) => {
+                let result = verify_file(
+                    agent,
+                    file,
+                    &validators,
+                    store,
+                    &multi,
+                    verification.label(),
+                );
+                record(cache, file, &result);
+                result.map(|(outcome, _)| outcome)
+            }
+        };
+
+        if let Err(e) = &result {
+            multi
+                .println(format!("Failed {}: {e}", verification.label()))
+                .ok();
+        }
+        overall.inc(1);
+
+        result
+    });
+
+    overall.finish_and_clear();
+
+    files.into_iter().zip(results).collect()
+}
+
+fn record(
+    cache: Option<&Mutex<VerificationCache>>,
+    file: &FileDef,
+    result: &Result<(VerifyOutcome, Validators), String>,
+) {
+    let Some(cache) = cache else {
+        return;
+    };
+
+    let mut cache = cache.lock().unwrap();
+    match result {
+        Ok((_, validators)) => cache.insert(file, validators.clone()),
+        Err(_) => cache.remove(file),
+    }
+}
+
+/// Download and hash a file without keeping it, showing progress in its own
+/// bar while in flight.
+///
+/// The download is skipped if the server reports the file unchanged since it
+/// was validated with `validators`. Validators to record for the file are
+/// returned along with the outcome.
+fn verify_file(
+    agent: &Agent,
+    file: &FileDef,
+    validators: &Validators,
+    store: Option<&DownloadCache>,
+    multi: &MultiProgress,
+    label: String,
+) -> Result<(VerifyOutcome, Validators), String> {
+    let mut req = agent.get(file.url.as_str());
+    if let Some(etag) = &validators.etag {
+        req = req.set(IF_NONE_MATCH.as_str(), etag);
+    }
+    if let Some(last_modified) = &validators.last_modified {
+        req = req.set(IF_MODIFIED_SINCE.as_str(), last_modified);
+    }
+    let resp = req.call().map_err(|e| e.to_string())?;
+
+    if resp.status() == 304 && !validators.is_empty() {
+        return Ok((VerifyOutcome::NotModified, validators.clone()));
+    }
+
+    let new_validators = Validators {
+        etag: resp.header(ETAG.as_str()).map(str::to_string),
+        last_modified: resp.header(LAST_MODIFIED.as_str()).map(str::to_string),
+    };
+    let file_name = response_file_name(&resp);
+
+    let size = resp
+        .header(CONTENT_LENGTH.as_str())
+        .and_then(|i| i.parse::<u64>().ok());
+    let pb = multi.add(file_progress_bar(size, label.clone()));
+
+    let reader = pb.wrap_read(resp.into_reader());
+    let hashed = match store {
+        Some(store) => store
+            .temp_file()
+            .and_then(|temp| hash_into(reader, temp))
+            .map(|(temp, hash, _)| (Some(temp), hash)),
+        None => hash_into(reader, io::sink()).map(|(_, hash, _)| (None, hash)),
+    };
+    multi.remove(&pb);
+    let (temp, hash) = hashed.map_err(|e| e.to_string())?;
+
+    check_hash(file, &hash)?;
+
+    if let (Some(store), Some(temp)) = (store, temp) {
+        let cached = temp
+            .into_verified(file_name)
+            .map_err(Into::into)
+            .and_then(|verified| store.commit(verified, file, new_validators.clone()));
+        if let Err(e) = cached {
+            multi.println(format!("Failed to cache {label}: {e}")).ok();
+        }
+    }
+
+    Ok((VerifyOutcome::Downloaded, new_validators))
+}
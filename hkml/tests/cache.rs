@@ -0,0 +1,205 @@
+mod common;
+
+use std::fs::{self, File};
+use std::time::{Duration, SystemTime};
+
+use common::*;
+
+fn stdout(output: &std::process::Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn download_reuses_cached_files() {
+    let server = TestServer::new([("First.dll", b"first".to_vec())]);
+
+    let dir = temp_dir();
+    let mod_links = dir.path().join("ModLinks.xml");
+    let cache_dir = dir.path().join("cache");
+    write_mod_links(
+        &mod_links,
+        &[TestMod::new("First", server.url("First.dll"), b"first")],
+    );
+
+    let download = |out: &str| {
+        hkml(
+            dir.path(),
+            &[
+                "download",
+                "-i",
+                path_str(&mod_links),
+                "-o",
+                out,
+                "--cache-dir",
+                path_str(&cache_dir),
+                "First",
+            ],
+        )
+    };
+
+    let output = download("a.zip");
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(server.requests().len(), 1);
+
+    server.clear_requests();
+    let output = download("b.zip");
+    assert!(output.status.success(), "{output:?}");
+    assert!(server.requests().is_empty());
+    assert_eq!(
+        fs::read(dir.path().join("a.zip")).unwrap(),
+        fs::read(dir.path().join("b.zip")).unwrap()
+    );
+
+    let output = hkml(
+        dir.path(),
+        &["cache", "ls", "--cache-dir", path_str(&cache_dir)],
+    );
+    let listing = stdout(&output);
+    assert!(listing.contains(&sha256_hex(b"first")[..12]), "{listing}");
+    assert!(listing.contains("First.dll"), "{listing}");
+    assert!(listing.contains("1 file(s)"), "{listing}");
+}
+
+#[test]
+fn validate_fills_and_revalidates_from_store() {
+    let server = TestServer::new([("a.dll", b"first".to_vec())]);
+
+    let dir = temp_dir();
+    let mod_links = dir.path().join("ModLinks.xml");
+    let cache_dir = dir.path().join("cache");
+    write_mod_links(
+        &mod_links,
+        &[TestMod::new("First", server.url("a.dll"), b"first")],
+    );
+
+    let output = hkml(
+        dir.path(),
+        &[
+            "validate",
+            "-i",
+            path_str(&mod_links),
+            "--cache-dir",
+            path_str(&cache_dir),
+        ],
+    );
+    assert!(output.status.success(), "{output:?}");
+
+    // Only verifications are forgotten, the stored file provides validators
+    fs::remove_file(cache_dir.join("verified.json")).unwrap();
+    server.clear_requests();
+
+    let output = hkml(
+        dir.path(),
+        &[
+            "validate",
+            "-i",
+            path_str(&mod_links),
+            "--cache-dir",
+            path_str(&cache_dir),
+        ],
+    );
+    assert!(output.status.success(), "{output:?}");
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].header("If-None-Match").is_some());
+
+    // Downloads are served from what validate stored
+    server.clear_requests();
+    let output = hkml(
+        dir.path(),
+        &[
+            "download",
+            "-i",
+            path_str(&mod_links),
+            "-o",
+            "out.zip",
+            "--cache-dir",
+            path_str(&cache_dir),
+            "First",
+        ],
+    );
+    assert!(output.status.success(), "{output:?}");
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn cache_size_limit_and_gc() {
+    let server = TestServer::new([("a.dll", vec![b'a'; 600]), ("b.dll", vec![b'b'; 600])]);
+
+    let dir = temp_dir();
+    let mod_links = dir.path().join("ModLinks.xml");
+    let cache_dir = dir.path().join("cache");
+    write_mod_links(
+        &mod_links,
+        &[
+            TestMod::new("A", server.url("a.dll"), &[b'a'; 600]),
+            TestMod::new("B", server.url("b.dll"), &[b'b'; 600]),
+        ],
+    );
+
+    for name in ["A", "B"] {
+        let output = hkml(
+            dir.path(),
+            &[
+                "download",
+                "-i",
+                path_str(&mod_links),
+                "-o",
+                &format!("{name}.zip"),
+                "--cache-dir",
+                path_str(&cache_dir),
+                "--cache-max-size",
+                "1KB",
+                name,
+            ],
+        );
+        assert!(output.status.success(), "{output:?}");
+    }
+
+    // The least recently used file was evicted to stay within 1000 bytes
+    let ls = || {
+        stdout(&hkml(
+            dir.path(),
+            &["cache", "ls", "--cache-dir", path_str(&cache_dir)],
+        ))
+    };
+    let listing = ls();
+    assert!(listing.contains("b.dll"), "{listing}");
+    assert!(!listing.contains("a.dll"), "{listing}");
+
+    // Abandoned downloads go as well, unlike ones that may still be resumed
+    let partial = cache_dir.join("partial");
+    fs::create_dir_all(&partial).unwrap();
+    let stale = partial.join("stale.tmp");
+    let fresh = partial.join("fresh.tmp");
+    fs::write(&stale, b"stale").unwrap();
+    fs::write(&fresh, b"fresh").unwrap();
+    File::options()
+        .write(true)
+        .open(&stale)
+        .unwrap()
+        .set_modified(SystemTime::now() - Duration::from_secs(2 * 60 * 60))
+        .unwrap();
+
+    let output = hkml(
+        dir.path(),
+        &[
+            "cache",
+            "gc",
+            "--cache-dir",
+            path_str(&cache_dir),
+            "--max-size",
+            "0",
+        ],
+    );
+    assert!(stdout(&output).contains("Removed 1 file(s)"), "{output:?}");
+    assert!(ls().contains("0 file(s)"));
+    assert!(!stale.exists());
+    assert!(fresh.exists());
+
+    let output = hkml(
+        dir.path(),
+        &["cache", "clear", "--cache-dir", path_str(&cache_dir)],
+    );
+    assert!(output.status.success(), "{output:?}");
+}
@@ -0,0 +1,346 @@
+#![allow(dead_code)]
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{Read, Write as _};
+use std::net::{Shutdown, TcpListener};
+use std::path::Path;
+use std::process::{Command, Output};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+
+use tiny_http::{Header, Response, Server};
+
+/// A request received by [`TestServer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Local HTTP stand-in serving fixed files, recording every request.
+/// Requests are handled concurrently.
+///
+/// Files are served with their hash as ETag, conditional requests for
+/// unchanged files are answered with 304 and range requests with 206.
+pub struct TestServer {
+    pub base_url: String,
+    files: Files,
+    requests: Arc<Mutex<Vec<Request>>>,
+    failures: Failures,
+    delays: Delays,
+    cut_offs: CutOffs,
+}
+
+type Files = Arc<Mutex<BTreeMap<String, Vec<u8>>>>;
+type Failures = Arc<Mutex<BTreeMap<String, (u16, usize)>>>;
+type Delays = Arc<Mutex<BTreeMap<String, Duration>>>;
+type CutOffs = Arc<Mutex<BTreeMap<String, usize>>>;
+
+/// Serve a single request on a new port with the first `len` bytes of the
+/// body, then close the connection. Returns the address.
+///
+/// tiny_http keeps connections open no matter what, so the client would
+/// wait for the rest of the body forever.
+fn serve_cut_off(body: Vec<u8>, etag: String, len: usize) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+
+        // Read the whole request, so closing does not reset the connection
+        let mut request = vec![];
+        let mut buf = [0; 1024];
+        while !request.ends_with(b"\r\n\r\n") {
+            match stream.read(&mut buf) {
+                Ok(0) | Err(_) => return,
+                Ok(n) => request.extend_from_slice(&buf[..n]),
+            }
+        }
+
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/octet-stream\r\nContent-Length: {}\r\nETag: {etag}\r\n\r\n",
+            body.len()
+        )
+        .unwrap();
+        stream.write_all(&body[..len.min(body.len())]).ok();
+        stream.shutdown(Shutdown::Both).ok();
+    });
+
+    addr
+}
+
+/// Start of a `Range: bytes=N-` request, unless `If-Range` does not match.
+fn range_start(request: &tiny_http::Request, etag: &str) -> Option<usize> {
+    let header = |name: &str| {
+        request
+            .headers()
+            .iter()
+            .find(|h| h.field.to_string().eq_ignore_ascii_case(name))
+            .map(|h| h.value.as_str())
+    };
+
+    if header("If-Range").is_some_and(|v| v != etag) {
+        return None;
+    }
+
+    header("Range")?
+        .strip_prefix("bytes=")?
+        .strip_suffix('-')?
+        .parse()
+        .ok()
+}
+
+fn respond(
+    request: tiny_http::Request,
+    files: &Files,
+    requests: &Mutex<Vec<Request>>,
+    failures: &Failures,
+    delays: &Delays,
+    cut_offs: &CutOffs,
+) {
+    requests.lock().unwrap().push(Request {
+        path: request.url().to_string(),
+        headers: request
+            .headers()
+            .iter()
+            .map(|h| (h.field.to_string(), h.value.to_string()))
+            .collect(),
+    });
+
+    if let Some(delay) = delays.lock().unwrap().get(request.url()).copied() {
+        thread::sleep(delay);
+    }
+
+    let failure = match failures.lock().unwrap().get_mut(request.url()) {
+        Some((status, remaining)) if *remaining > 0 => {
+            *remaining -= 1;
+            Some(*status)
+        }
+        _ => None,
+    };
+    if let Some(status) = failure {
+        request
+            .respond(Response::from_data(b"Failure".to_vec()).with_status_code(status))
+            .ok();
+        return;
+    }
+
+    let Some(body) = files.lock().unwrap().get(request.url()).cloned() else {
+        request
+            .respond(Response::from_data(b"Not Found".to_vec()).with_status_code(404))
+            .ok();
+        return;
+    };
+
+    let etag = format!("\"{}\"", sha256_hex(&body));
+    if let Some(len) = cut_offs.lock().unwrap().remove(request.url()) {
+        let addr = serve_cut_off(body, etag, len);
+        let location = format!("http://{addr}{}", request.url());
+        request
+            .respond(
+                Response::empty(302).with_header(Header::from_bytes("Location", location).unwrap()),
+            )
+            .ok();
+        return;
+    }
+
+    let not_modified = request
+        .headers()
+        .iter()
+        .any(|h| h.field.equiv("If-None-Match") && h.value.as_str() == etag);
+    let total = body.len();
+    let (status, body, content_range) = match range_start(&request, &etag) {
+        _ if not_modified => (304, vec![], None),
+        Some(start) if start >= total => {
+            let content_range = format!("bytes */{total}");
+            (416, vec![], Some(content_range))
+        }
+        Some(start) => {
+            let content_range = format!("bytes {start}-{}/{total}", total - 1);
+            (206, body[start..].to_vec(), Some(content_range))
+        }
+        None => (200, body, None),
+    };
+
+    let mut response = Response::from_data(body)
+        .with_status_code(status)
+        .with_header(Header::from_bytes("Content-Type", "application/octet-stream").unwrap())
+        .with_header(Header::from_bytes("ETag", etag).unwrap());
+    if let Some(content_range) = content_range {
+        response.add_header(Header::from_bytes("Content-Range", content_range).unwrap());
+    }
+    request.respond(response).ok();
+}
+
+impl TestServer {
+    pub fn new(files: impl IntoIterator<Item = (&'static str, Vec<u8>)>) -> Self {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", server.server_addr().to_ip().unwrap());
+
+        let files = Arc::new(Mutex::new(
+            files
+                .into_iter()
+                .map(|(path, body)| (format!("/{path}"), body))
+                .collect::<BTreeMap<_, _>>(),
+        ));
+        let requests = Arc::new(Mutex::new(vec![]));
+        let failures = Arc::new(Mutex::new(BTreeMap::new()));
+        let delays = Arc::new(Mutex::new(BTreeMap::new()));
+        let cut_offs = Arc::new(Mutex::new(BTreeMap::new()));
+
+        {
+            let files = files.clone();
+            let requests = requests.clone();
+            let failures = failures.clone();
+            let delays = delays.clone();
+            let cut_offs = cut_offs.clone();
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    let files = files.clone();
+                    let requests = requests.clone();
+                    let failures = failures.clone();
+                    let delays = delays.clone();
+                    let cut_offs = cut_offs.clone();
+                    thread::spawn(move || {
+                        respond(request, &files, &requests, &failures, &delays, &cut_offs)
+                    });
+                }
+            });
+        }
+
+        Self {
+            base_url,
+            files,
+            requests,
+            failures,
+            delays,
+            cut_offs,
+        }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}/{path}", self.base_url)
+    }
+
+    pub fn set_file(&self, path: &str, body: Vec<u8>) {
+        self.files.lock().unwrap().insert(format!("/{path}"), body);
+    }
+
+    /// Respond to the next `count` requests for the path with the status.
+    pub fn fail_next(&self, path: &str, status: u16, count: usize) {
+        self.failures
+            .lock()
+            .unwrap()
+            .insert(format!("/{path}"), (status, count));
+    }
+
+    /// Wait before responding to requests for the path.
+    pub fn set_delay(&self, path: &str, delay: Duration) {
+        self.delays
+            .lock()
+            .unwrap()
+            .insert(format!("/{path}"), delay);
+    }
+
+    /// Drop the connection of the next response for the path after `len`
+    /// bytes of the body. The response is redirected to another port for
+    /// that, which is not recorded as a request.
+    pub fn cut_off_next(&self, path: &str, len: usize) {
+        self.cut_offs
+            .lock()
+            .unwrap()
+            .insert(format!("/{path}"), len);
+    }
+
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    pub fn clear_requests(&self) {
+        self.requests.lock().unwrap().clear();
+    }
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    hex::encode_upper(Sha256::digest(data))
+}
+
+/// A mod in a modlinks written by [`write_mod_links`].
+pub struct TestMod<'a> {
+    pub name: &'a str,
+    pub version: &'a str,
+    pub url: String,
+    pub sha256: String,
+    pub dependencies: &'a [&'a str],
+}
+
+impl<'a> TestMod<'a> {
+    pub fn new(name: &'a str, url: String, content: &[u8]) -> Self {
+        Self {
+            name,
+            version: "1.0.0.0",
+            url,
+            sha256: sha256_hex(content),
+            dependencies: &[],
+        }
+    }
+}
+
+pub fn write_mod_links(path: &Path, mods: &[TestMod<'_>]) {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<ModLinks xmlns=\"https://github.com/HollowKnight-Modding/HollowKnight.ModLinks/HollowKnight.ModManager\">\n",
+    );
+
+    for m in mods {
+        write!(
+            xml,
+            "\t<Manifest>\n\t\t<Name>{}</Name>\n\t\t<Description>{} mod</Description>\n\t\t<Version>{}</Version>\n\t\t<Link SHA256=\"{}\"><![CDATA[{}]]></Link>\n\t\t<Dependencies>\n",
+            m.name, m.name, m.version, m.sha256, m.url
+        )
+        .unwrap();
+        for dep in m.dependencies {
+            writeln!(xml, "\t\t\t<Dependency>{dep}</Dependency>").unwrap();
+        }
+        writeln!(
+            xml,
+            "\t\t</Dependencies>\n\t\t<Repository><![CDATA[https://github.com/example/{}]]></Repository>\n\t</Manifest>",
+            m.name
+        )
+        .unwrap();
+    }
+
+    xml.push_str("</ModLinks>\n");
+    fs::write(path, xml).unwrap();
+}
+
+pub fn hkml(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_hkml"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+pub fn temp_dir() -> tempfile::TempDir {
+    tempfile::tempdir().unwrap()
+}
+
+pub fn path_str(path: &Path) -> &str {
+    path.to_str().unwrap()
+}
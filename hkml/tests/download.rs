@@ -0,0 +1,335 @@
+mod common;
+
+use std::fs::File;
+use std::process::Output;
+use std::time::Duration;
+
+use zip::ZipArchive;
+
+use common::*;
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn retries_transient_failures() {
+    let server = TestServer::new([("First.dll", b"first".to_vec())]);
+    server.fail_next("First.dll", 503, 2);
+
+    let dir = temp_dir();
+    let mod_links = dir.path().join("ModLinks.xml");
+    write_mod_links(
+        &mod_links,
+        &[TestMod::new("First", server.url("First.dll"), b"first")],
+    );
+
+    let output = hkml(
+        dir.path(),
+        &[
+            "download",
+            "-i",
+            path_str(&mod_links),
+            "-o",
+            "out.zip",
+            "--retries",
+            "2",
+            "First",
+        ],
+    );
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(server.requests().len(), 3);
+    assert!(stdout(&output).contains("Downloaded 1 mod(s), 0 failed"));
+}
+
+#[test]
+fn summarizes_failures_without_retrying_permanent_ones() {
+    let server = TestServer::new([
+        ("Good.dll", b"good".to_vec()),
+        ("Flaky.dll", b"flaky".to_vec()),
+    ]);
+    server.fail_next("Flaky.dll", 500, 10);
+
+    let dir = temp_dir();
+    let mod_links = dir.path().join("ModLinks.xml");
+    let out = dir.path().join("out");
+    write_mod_links(
+        &mod_links,
+        &[
+            TestMod::new("Flaky", server.url("Flaky.dll"), b"flaky"),
+            TestMod::new("Good", server.url("Good.dll"), b"good"),
+            TestMod::new("Missing", server.url("Missing.dll"), b"missing"),
+        ],
+    );
+
+    let output = hkml(
+        dir.path(),
+        &[
+            "download",
+            "-i",
+            path_str(&mod_links),
+            "-o",
+            path_str(&out),
+            "--unpack",
+            "--retries",
+            "1",
+            "Flaky",
+            "Good",
+            "Missing",
+        ],
+    );
+    assert!(!output.status.success());
+
+    let stdout = stdout(&output);
+    assert!(stdout.contains("Downloaded 1 mod(s), 2 failed"), "{stdout}");
+    assert!(stdout.contains("  Flaky: "), "{stdout}");
+    assert!(stdout.contains("  Missing: "), "{stdout}");
+    assert_eq!(std::fs::read(out.join("Good.dll")).unwrap(), b"good");
+
+    let count = |path: &str| server.requests().iter().filter(|r| r.path == path).count();
+    assert_eq!(count("/Flaky.dll"), 2);
+    assert_eq!(count("/Missing.dll"), 1);
+}
+
+#[test]
+fn repack_is_in_install_order() {
+    let server = TestServer::new([
+        ("App.dll", b"app".to_vec()),
+        ("Core.dll", b"core".to_vec()),
+        ("Extra.dll", b"extra".to_vec()),
+        ("Lib.dll", b"lib".to_vec()),
+    ]);
+    // Dependencies finish last, but are still written first
+    server.set_delay("Core.dll", Duration::from_millis(300));
+    server.set_delay("Lib.dll", Duration::from_millis(150));
+
+    let dir = temp_dir();
+    let mod_links = dir.path().join("ModLinks.xml");
+    let out = dir.path().join("mods.zip");
+    write_mod_links(
+        &mod_links,
+        &[
+            TestMod {
+                dependencies: &["Lib"],
+                ..TestMod::new("App", server.url("App.dll"), b"app")
+            },
+            TestMod::new("Core", server.url("Core.dll"), b"core"),
+            TestMod::new("Extra", server.url("Extra.dll"), b"extra"),
+            TestMod {
+                dependencies: &["Core"],
+                ..TestMod::new("Lib", server.url("Lib.dll"), b"lib")
+            },
+        ],
+    );
+
+    let output = hkml(
+        dir.path(),
+        &[
+            "download",
+            "-i",
+            path_str(&mod_links),
+            "-o",
+            path_str(&out),
+            "--repack",
+            "--jobs",
+            "4",
+            "App",
+            "Extra",
+        ],
+    );
+    assert!(output.status.success(), "{output:?}");
+
+    let mut zip = ZipArchive::new(File::open(out).unwrap()).unwrap();
+    let names = (0..zip.len())
+        .map(|i| zip.by_index(i).unwrap().name().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        [
+            "Core/",
+            "Core/Core.dll",
+            "Extra/",
+            "Extra/Extra.dll",
+            "Lib/",
+            "Lib/Lib.dll",
+            "App/",
+            "App/App.dll",
+        ]
+    );
+}
+
+#[test]
+fn hash_mismatch_leaves_no_partial_files() {
+    let server = TestServer::new([
+        ("Bad.dll", b"tampered".to_vec()),
+        ("Good.dll", b"good".to_vec()),
+    ]);
+
+    let dir = temp_dir();
+    let mod_links = dir.path().join("ModLinks.xml");
+    let out = dir.path().join("out");
+    std::fs::create_dir(&out).unwrap();
+    write_mod_links(
+        &mod_links,
+        &[
+            TestMod::new("Bad", server.url("Bad.dll"), b"original"),
+            TestMod::new("Good", server.url("Good.dll"), b"good"),
+        ],
+    );
+
+    for name in ["Bad", "Good"] {
+        hkml(
+            dir.path(),
+            &[
+                "download",
+                "-i",
+                path_str(&mod_links),
+                "-o",
+                path_str(&out.join(format!("{name}.zip"))),
+                name,
+            ],
+        );
+    }
+
+    let mut names = std::fs::read_dir(&out)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, ["Good.zip"]);
+
+    // Wrapped in a zip commented with the hash of the original file
+    let zip = ZipArchive::new(File::open(out.join("Good.zip")).unwrap()).unwrap();
+    assert_eq!(zip.comment(), sha256_hex(b"good").as_bytes());
+}
+
+/// Content large enough to be cut off halfway through.
+fn large_content() -> Vec<u8> {
+    (0..256 * 1024).map(|i| (i * 31 % 251) as u8).collect()
+}
+
+fn download_large(dir: &std::path::Path, mod_links: &std::path::Path, retries: &str) -> Output {
+    hkml(
+        dir,
+        &[
+            "download",
+            "-i",
+            path_str(mod_links),
+            "-o",
+            "out",
+            "--unpack",
+            "--retries",
+            retries,
+            "Large",
+        ],
+    )
+}
+
+#[test]
+fn resumes_interrupted_download() {
+    let content = large_content();
+    let server = TestServer::new([("Large.dll", content.clone())]);
+    server.cut_off_next("Large.dll", 100 * 1024);
+
+    let dir = temp_dir();
+    let mod_links = dir.path().join("ModLinks.xml");
+    write_mod_links(
+        &mod_links,
+        &[TestMod::new("Large", server.url("Large.dll"), &content)],
+    );
+
+    let output = download_large(dir.path(), &mod_links, "1");
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        std::fs::read(dir.path().join("out/Large.dll")).unwrap(),
+        content
+    );
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].header("Range"), None);
+    let range = requests[1].header("Range").unwrap();
+    assert!(
+        range.starts_with("bytes=") && range != "bytes=0-",
+        "{range}"
+    );
+    assert_eq!(
+        requests[1].header("If-Range"),
+        Some(format!("\"{}\"", sha256_hex(&content)).as_str())
+    );
+    // Offsets count bytes as sent, so the body must not be compressed
+    assert!(requests
+        .iter()
+        .all(|request| request.header("Accept-Encoding") == Some("identity")));
+    assert!(!dir.path().join(".hkml-partial").exists());
+}
+
+#[test]
+fn resumes_partial_download_of_previous_run() {
+    let content = large_content();
+    let server = TestServer::new([("Large.dll", content.clone())]);
+    server.cut_off_next("Large.dll", 100 * 1024);
+
+    let dir = temp_dir();
+    let mod_links = dir.path().join("ModLinks.xml");
+    write_mod_links(
+        &mod_links,
+        &[TestMod::new("Large", server.url("Large.dll"), &content)],
+    );
+
+    let output = download_large(dir.path(), &mod_links, "0");
+    assert!(!output.status.success());
+    let part = dir
+        .path()
+        .join(format!(".hkml-partial/{}.part", sha256_hex(&content)));
+    assert!(part.exists());
+
+    server.clear_requests();
+    let output = download_large(dir.path(), &mod_links, "0");
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        std::fs::read(dir.path().join("out/Large.dll")).unwrap(),
+        content
+    );
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].header("Range").is_some());
+    assert!(!dir.path().join(".hkml-partial").exists());
+}
+
+#[test]
+fn corrupted_partial_download_is_downloaded_again() {
+    let content = large_content();
+    let server = TestServer::new([("Large.dll", content.clone())]);
+    server.cut_off_next("Large.dll", 100 * 1024);
+
+    let dir = temp_dir();
+    let mod_links = dir.path().join("ModLinks.xml");
+    write_mod_links(
+        &mod_links,
+        &[TestMod::new("Large", server.url("Large.dll"), &content)],
+    );
+
+    let output = download_large(dir.path(), &mod_links, "0");
+    assert!(!output.status.success());
+    let part = dir
+        .path()
+        .join(format!(".hkml-partial/{}.part", sha256_hex(&content)));
+    let mut corrupted = std::fs::read(&part).unwrap();
+    corrupted[0] ^= 0xFF;
+    std::fs::write(&part, corrupted).unwrap();
+
+    server.clear_requests();
+    let output = download_large(dir.path(), &mod_links, "0");
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        std::fs::read(dir.path().join("out/Large.dll")).unwrap(),
+        content
+    );
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[0].header("Range").is_some());
+    assert_eq!(requests[1].header("Range"), None);
+}
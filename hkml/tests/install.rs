@@ -0,0 +1,402 @@
+mod common;
+
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::process::Output;
+
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+use common::*;
+
+fn zip_of(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut zip = ZipWriter::new(Cursor::new(vec![]));
+    for (name, content) in files {
+        zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+        zip.write_all(content).unwrap();
+    }
+    zip.finish().unwrap().into_inner()
+}
+
+/// Game directory with a mod the user put there by hand.
+fn fake_game(dir: &Path) -> PathBuf {
+    let game = dir.join("Hollow Knight");
+    let user_mod = game.join("hollow_knight_Data/Managed/Mods/UserMod");
+    fs::create_dir_all(&user_mod).unwrap();
+    fs::write(user_mod.join("UserMod.dll"), b"user").unwrap();
+    game
+}
+
+fn mods_dir(game: &Path) -> PathBuf {
+    game.join("hollow_knight_Data/Managed/Mods")
+}
+
+fn run(dir: &Path, command: &str, game: &Path, args: &[&str]) -> Output {
+    let mut all = vec![command, "-g", path_str(game)];
+    if command != "uninstall" {
+        all.extend(["-i", "ModLinks.xml"]);
+    }
+    all.extend(args);
+    hkml(dir, &all)
+}
+
+fn installed(game: &Path) -> serde_json::Value {
+    serde_json::from_slice(&fs::read(mods_dir(game).join("hkml-installed.json")).unwrap()).unwrap()
+}
+
+#[test]
+fn install_extracts_mods_with_dependencies() {
+    let app = zip_of(&[("App.dll", b"app"), ("Assets/readme.txt", b"hello")]);
+    let server = TestServer::new([("App.zip", app.clone()), ("Lib.dll", b"lib".to_vec())]);
+
+    let dir = temp_dir();
+    let game = fake_game(dir.path());
+    write_mod_links(
+        &dir.path().join("ModLinks.xml"),
+        &[
+            TestMod {
+                dependencies: &["Lib"],
+                ..TestMod::new("App", server.url("App.zip"), &app)
+            },
+            TestMod::new("Lib", server.url("Lib.dll"), b"lib"),
+        ],
+    );
+
+    let output = run(dir.path(), "install", &game, &["App"]);
+    assert!(output.status.success(), "{output:?}");
+
+    let mods = mods_dir(&game);
+    assert_eq!(fs::read(mods.join("App/App.dll")).unwrap(), b"app");
+    assert_eq!(
+        fs::read(mods.join("App/Assets/readme.txt")).unwrap(),
+        b"hello"
+    );
+    assert_eq!(fs::read(mods.join("Lib/Lib.dll")).unwrap(), b"lib");
+    assert_eq!(fs::read(mods.join("UserMod/UserMod.dll")).unwrap(), b"user");
+
+    let installed = installed(&game);
+    assert_eq!(installed["App"]["version"], "1.0.0.0");
+    assert_eq!(installed["App"]["sha256"], sha256_hex(&app));
+    assert_eq!(installed["App"]["dependencies"][0], "Lib");
+    assert_eq!(installed["Lib"]["sha256"], sha256_hex(b"lib"));
+    assert!(installed.get("UserMod").is_none());
+
+    // Nothing left behind next to the mods
+    let mut names = fs::read_dir(&mods)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, ["App", "Lib", "UserMod", "hkml-installed.json"]);
+    assert!(!mods.with_file_name(".hkml-partial").exists());
+}
+
+#[test]
+fn install_skips_directories_not_installed_by_hkml() {
+    let server = TestServer::new([("UserMod.dll", b"new".to_vec())]);
+
+    let dir = temp_dir();
+    let game = fake_game(dir.path());
+    write_mod_links(
+        &dir.path().join("ModLinks.xml"),
+        &[TestMod::new("UserMod", server.url("UserMod.dll"), b"new")],
+    );
+
+    let output = run(dir.path(), "install", &game, &["UserMod"]);
+    assert!(output.status.success(), "{output:?}");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("was not installed by hkml"));
+    assert!(server.requests().is_empty());
+
+    let user_mod = mods_dir(&game).join("UserMod");
+    assert_eq!(fs::read(user_mod.join("UserMod.dll")).unwrap(), b"user");
+
+    let output = run(dir.path(), "install", &game, &["UserMod", "--force"]);
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(fs::read(user_mod.join("UserMod.dll")).unwrap(), b"new");
+    assert_eq!(installed(&game)["UserMod"]["sha256"], sha256_hex(b"new"));
+}
+
+#[test]
+fn install_rejects_mod_names_escaping_mods_dir() {
+    let server = TestServer::new([("App.dll", b"app".to_vec()), ("Evil.dll", b"evil".to_vec())]);
+
+    let dir = temp_dir();
+    let game = fake_game(dir.path());
+    let evil = "Evil/../../../../../Escaped";
+    write_mod_links(
+        &dir.path().join("ModLinks.xml"),
+        &[
+            TestMod {
+                dependencies: &[evil],
+                ..TestMod::new("App", server.url("App.dll"), b"app")
+            },
+            TestMod::new(evil, server.url("Evil.dll"), b"evil"),
+        ],
+    );
+
+    let output = run(dir.path(), "install", &game, &["App", "--force"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains(&format!("Invalid mod name: {evil}")));
+    assert!(String::from_utf8_lossy(&output.stderr).contains(&format!("Failed to install {evil}")));
+
+    // The others are still installed
+    assert_eq!(
+        fs::read(mods_dir(&game).join("App/App.dll")).unwrap(),
+        b"app"
+    );
+    let paths = server
+        .requests()
+        .into_iter()
+        .map(|r| r.path)
+        .collect::<Vec<_>>();
+    assert_eq!(paths, ["/App.dll"]);
+    assert!(!dir.path().join("Escaped").exists());
+    assert!(installed(&game).get(evil).is_none());
+
+    let victim = dir.path().join("Victim");
+    fs::create_dir(&victim).unwrap();
+    let record = serde_json::json!({
+        "../../../../Victim": { "version": "1.0.0.0", "sha256": sha256_hex(b"victim") },
+    });
+    fs::write(
+        mods_dir(&game).join("hkml-installed.json"),
+        record.to_string(),
+    )
+    .unwrap();
+
+    let output = run(dir.path(), "uninstall", &game, &["../../../../Victim"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid mod name"));
+    assert!(victim.exists());
+}
+
+#[test]
+fn update_replaces_changed_mods_and_installs_new_dependencies() {
+    let server = TestServer::new([("App.dll", b"app".to_vec()), ("Lib.dll", b"lib".to_vec())]);
+
+    let dir = temp_dir();
+    let game = fake_game(dir.path());
+    let mod_links = dir.path().join("ModLinks.xml");
+    write_mod_links(
+        &mod_links,
+        &[
+            TestMod::new("App", server.url("App.dll"), b"app"),
+            TestMod::new("Lib", server.url("Lib.dll"), b"lib"),
+        ],
+    );
+
+    let output = run(dir.path(), "install", &game, &["App", "Lib"]);
+    assert!(output.status.success(), "{output:?}");
+
+    server.set_file("App2.dll", b"app 2".to_vec());
+    server.set_file("New.dll", b"new".to_vec());
+    write_mod_links(
+        &mod_links,
+        &[
+            TestMod {
+                version: "1.1.0.0",
+                dependencies: &["New"],
+                ..TestMod::new("App", server.url("App2.dll"), b"app 2")
+            },
+            TestMod::new("Lib", server.url("Lib.dll"), b"lib"),
+            TestMod::new("New", server.url("New.dll"), b"new"),
+        ],
+    );
+    server.clear_requests();
+
+    let output = run(dir.path(), "update", &game, &[]);
+    assert!(output.status.success(), "{output:?}");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Updated App 1.0.0.0 -> 1.1.0.0"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("Installed New 1.0.0.0"), "{stdout}");
+    assert!(stdout.contains("Lib 1.0.0.0 is up to date"), "{stdout}");
+
+    let mods = mods_dir(&game);
+    assert_eq!(fs::read(mods.join("App/App2.dll")).unwrap(), b"app 2");
+    assert!(!mods.join("App/App.dll").exists());
+    assert_eq!(fs::read(mods.join("New/New.dll")).unwrap(), b"new");
+
+    let mut paths = server
+        .requests()
+        .into_iter()
+        .map(|r| r.path)
+        .collect::<Vec<_>>();
+    paths.sort();
+    assert_eq!(paths, ["/App2.dll", "/New.dll"]);
+
+    let installed = installed(&game);
+    assert_eq!(installed["App"]["version"], "1.1.0.0");
+    assert_eq!(installed["New"]["version"], "1.0.0.0");
+}
+
+#[test]
+fn update_skips_mods_with_missing_dependencies() {
+    let server = TestServer::new([("App.dll", b"app".to_vec()), ("Lib.dll", b"lib".to_vec())]);
+
+    let dir = temp_dir();
+    let game = fake_game(dir.path());
+    let mod_links = dir.path().join("ModLinks.xml");
+    write_mod_links(
+        &mod_links,
+        &[
+            TestMod::new("App", server.url("App.dll"), b"app"),
+            TestMod::new("Lib", server.url("Lib.dll"), b"lib"),
+        ],
+    );
+
+    let output = run(dir.path(), "install", &game, &["App", "Lib"]);
+    assert!(output.status.success(), "{output:?}");
+
+    server.set_file("App2.dll", b"app 2".to_vec());
+    server.set_file("Lib2.dll", b"lib 2".to_vec());
+    write_mod_links(
+        &mod_links,
+        &[
+            TestMod {
+                version: "1.1.0.0",
+                dependencies: &["Gone"],
+                ..TestMod::new("App", server.url("App2.dll"), b"app 2")
+            },
+            TestMod {
+                version: "1.1.0.0",
+                ..TestMod::new("Lib", server.url("Lib2.dll"), b"lib 2")
+            },
+        ],
+    );
+    server.clear_requests();
+
+    let output = run(dir.path(), "update", &game, &[]);
+    assert!(output.status.success(), "{output:?}");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Skipped App, missing dependency Gone"),
+        "{stderr}"
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Updated Lib 1.0.0.0 -> 1.1.0.0"),
+        "{stdout}"
+    );
+
+    let mods = mods_dir(&game);
+    assert_eq!(fs::read(mods.join("App/App.dll")).unwrap(), b"app");
+    assert_eq!(fs::read(mods.join("Lib/Lib2.dll")).unwrap(), b"lib 2");
+
+    let paths = server
+        .requests()
+        .into_iter()
+        .map(|r| r.path)
+        .collect::<Vec<_>>();
+    assert_eq!(paths, ["/Lib2.dll"]);
+
+    let installed = installed(&game);
+    assert_eq!(installed["App"]["version"], "1.0.0.0");
+    assert_eq!(installed["Lib"]["version"], "1.1.0.0");
+}
+
+#[test]
+fn uninstall_removes_only_recorded_mods() {
+    let server = TestServer::new([("App.dll", b"app".to_vec()), ("Lib.dll", b"lib".to_vec())]);
+
+    let dir = temp_dir();
+    let game = fake_game(dir.path());
+    write_mod_links(
+        &dir.path().join("ModLinks.xml"),
+        &[
+            TestMod {
+                dependencies: &["Lib"],
+                ..TestMod::new("App", server.url("App.dll"), b"app")
+            },
+            TestMod::new("Lib", server.url("Lib.dll"), b"lib"),
+        ],
+    );
+
+    let output = run(dir.path(), "install", &game, &["App"]);
+    assert!(output.status.success(), "{output:?}");
+
+    let output = run(dir.path(), "uninstall", &game, &["Lib"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("required by App"));
+
+    let output = run(dir.path(), "uninstall", &game, &["UserMod"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("was not installed by hkml"));
+
+    let output = run(dir.path(), "uninstall", &game, &["App", "Lib"]);
+    assert!(output.status.success(), "{output:?}");
+
+    let mods = mods_dir(&game);
+    assert!(!mods.join("App").exists());
+    assert!(!mods.join("Lib").exists());
+    assert_eq!(fs::read(mods.join("UserMod/UserMod.dll")).unwrap(), b"user");
+    assert_eq!(installed(&game), serde_json::json!({}));
+}
+
+#[test]
+fn outdated_lists_newer_versions_and_new_dependencies() {
+    let server = TestServer::new([
+        ("App.dll", b"app".to_vec()),
+        ("Lib.dll", b"lib".to_vec()),
+        ("New.dll", b"new".to_vec()),
+    ]);
+
+    let dir = temp_dir();
+    let game = fake_game(dir.path());
+    let mod_links = dir.path().join("ModLinks.xml");
+    write_mod_links(
+        &mod_links,
+        &[
+            TestMod::new("App", server.url("App.dll"), b"app"),
+            TestMod::new("Lib", server.url("Lib.dll"), b"lib"),
+        ],
+    );
+
+    let output = run(dir.path(), "install", &game, &["App", "Lib"]);
+    assert!(output.status.success(), "{output:?}");
+
+    write_mod_links(
+        &mod_links,
+        &[
+            TestMod {
+                version: "1.1.0.0",
+                dependencies: &["New"],
+                ..TestMod::new("App", server.url("App.dll"), b"app")
+            },
+            TestMod::new("Lib", server.url("Lib.dll"), b"lib"),
+            TestMod::new("New", server.url("New.dll"), b"new"),
+        ],
+    );
+
+    let output = run(dir.path(), "outdated", &game, &[]);
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Updates:\nApp 1.0.0.0 -> 1.1.0.0\nNew dependencies:\nNew\n"
+    );
+
+    let output = run(dir.path(), "install", &game, &["New"]);
+    assert!(output.status.success(), "{output:?}");
+
+    let output = run(dir.path(), "outdated", &game, &[]);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Updates:\nApp 1.0.0.0 -> 1.1.0.0\n"
+    );
+
+    // Same file as before, so only the record is updated
+    server.clear_requests();
+    let output = run(dir.path(), "update", &game, &[]);
+    assert!(output.status.success(), "{output:?}");
+    assert!(server.requests().is_empty());
+
+    let output = run(dir.path(), "outdated", &game, &[]);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "3 mod(s) up to date\n"
+    );
+}
@@ -0,0 +1,149 @@
+mod common;
+
+use common::*;
+
+#[test]
+fn parallel_verification_aggregates_failures() {
+    let server = TestServer::new([
+        ("a.dll", b"first".to_vec()),
+        ("b.dll", b"second".to_vec()),
+        ("c.dll", b"third".to_vec()),
+    ]);
+
+    let dir = temp_dir();
+    let mod_links = dir.path().join("ModLinks.xml");
+    let report = dir.path().join("report.json");
+
+    write_mod_links(
+        &mod_links,
+        &[
+            TestMod::new("First", server.url("a.dll"), b"first"),
+            TestMod::new("Missing", server.url("gone.dll"), b""),
+            TestMod::new("Mismatch", server.url("c.dll"), b"other"),
+            TestMod::new("Second", server.url("b.dll"), b"second"),
+        ],
+    );
+
+    let output = hkml(
+        dir.path(),
+        &[
+            "validate",
+            "-i",
+            path_str(&mod_links),
+            "--jobs",
+            "3",
+            "--report-format",
+            "json",
+            "--report-file",
+            path_str(&report),
+        ],
+    );
+
+    assert!(!output.status.success());
+
+    let report: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(report).unwrap()).unwrap();
+    assert_eq!(report["errors"], 2);
+
+    let issues = report["issues"].as_array().unwrap();
+    assert_eq!(issues[0]["mod_name"], "Mismatch");
+    assert_eq!(issues[0]["code"], "file-verification");
+    assert!(issues[0]["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("Hash mismatch"));
+    assert_eq!(issues[1]["mod_name"], "Missing");
+    assert!(issues[1]["message"].as_str().unwrap().contains("404"));
+
+    let mut paths = server
+        .requests()
+        .into_iter()
+        .map(|r| r.path)
+        .collect::<Vec<_>>();
+    paths.sort();
+    assert_eq!(paths, ["/a.dll", "/b.dll", "/c.dll", "/gone.dll"]);
+}
+
+#[test]
+fn successful_verification_exits_zero() {
+    let server = TestServer::new([("a.dll", b"first".to_vec())]);
+
+    let dir = temp_dir();
+    let mod_links = dir.path().join("ModLinks.xml");
+    write_mod_links(
+        &mod_links,
+        &[TestMod::new("First", server.url("a.dll"), b"first")],
+    );
+
+    let output = hkml(dir.path(), &["validate", "-i", path_str(&mod_links)]);
+    assert!(output.status.success(), "{output:?}");
+}
+
+#[test]
+fn verification_cache_skips_and_revalidates() {
+    let server = TestServer::new([("a.dll", b"first".to_vec()), ("b.dll", b"second".to_vec())]);
+
+    let dir = temp_dir();
+    let mod_links = dir.path().join("ModLinks.xml");
+    let cache_dir = dir.path().join("cache");
+    write_mod_links(
+        &mod_links,
+        &[
+            TestMod::new("First", server.url("a.dll"), b"first"),
+            TestMod::new("Second", server.url("b.dll"), b"second"),
+        ],
+    );
+
+    let validate = |max_age: &str| {
+        hkml(
+            dir.path(),
+            &[
+                "validate",
+                "-i",
+                path_str(&mod_links),
+                "--cache-dir",
+                path_str(&cache_dir),
+                "--max-age",
+                max_age,
+            ],
+        )
+    };
+
+    let output = validate("1h");
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(server.requests().len(), 2);
+    assert!(server
+        .requests()
+        .iter()
+        .all(|r| r.header("If-None-Match").is_none()));
+
+    server.clear_requests();
+    let output = validate("1h");
+    assert!(output.status.success(), "{output:?}");
+    assert!(server.requests().is_empty());
+
+    let output = validate("0s");
+    assert!(output.status.success(), "{output:?}");
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    let a = requests.iter().find(|r| r.path == "/a.dll").unwrap();
+    assert_eq!(
+        a.header("If-None-Match"),
+        Some(format!("\"{}\"", sha256_hex(b"first")).as_str())
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("2 not modified"));
+
+    // A changed file is downloaded again, fails and is evicted
+    server.set_file("a.dll", b"changed".to_vec());
+    server.clear_requests();
+    assert!(!validate("0s").status.success());
+    assert!(!validate("1h").status.success());
+    assert_eq!(
+        server
+            .requests()
+            .iter()
+            .filter(|r| r.path == "/a.dll")
+            .count(),
+        2
+    );
+}